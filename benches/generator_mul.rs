@@ -0,0 +1,100 @@
+use ark_ec::PrimeGroup;
+use ark_ff::UniformRand;
+use ark_grumpkin::{Fr, Projective};
+use ark_std::rand::{Rng, SeedableRng};
+use ark_std::test_rng;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand_chacha::ChaCha20Rng;
+use schnorr_tokens::ProxySignature;
+use schnorr_tokens::an23_proxy_signature::{
+    AN23ProxySignature, DelegationSpec, Parameters, SigningKey, SigningToken,
+};
+use schnorr_tokens::challenge_hash::{Blake2s, ChallengeHash, ChallengeInput};
+use schnorr_tokens::fixed_base::{DEFAULT_WINDOW, FixedBaseTable};
+
+fn bench_generator_mul(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let generator = Projective::generator();
+    let table = FixedBaseTable::new(generator, DEFAULT_WINDOW);
+    let scalar = Fr::rand(&mut rng);
+
+    let mut group = c.benchmark_group("generator_mul");
+    group.bench_function("double_and_add", |b| b.iter(|| generator * scalar));
+    group.bench_function("fixed_base_table", |b| b.iter(|| table.mul(&scalar)));
+    group.bench_function("table_precomputation", |b| {
+        b.iter(|| FixedBaseTable::new(generator, DEFAULT_WINDOW))
+    });
+    group.finish();
+}
+
+/// `delegate` without `bind_message_nonce`, with double-and-add for the generator
+/// multiplications instead of the table of `parameters`. It is sequential, so compare the two
+/// without the `parallel` feature.
+fn delegate_double_and_add<R: Rng>(
+    rng: &mut R,
+    parameters: &Parameters<Projective>,
+    sk: &SigningKey<Projective>,
+    number_of_tokens: u64,
+) -> (Vec<SigningToken<Projective>>, Vec<Fr>) {
+    let generator = parameters.generator();
+    let vk = (generator * sk.0).into();
+    let tokens = (0..number_of_tokens)
+        .map(|_| {
+            let mut rng = ChaCha20Rng::from_seed(rng.r#gen());
+            let m0 = Fr::rand(&mut rng);
+            let r0 = Fr::rand(&mut rng);
+            let c0 = <Blake2s as ChallengeHash<Projective>>::hash(&[
+                ChallengeInput::Field(m0),
+                ChallengeInput::Curve(vk),
+                ChallengeInput::Curve((generator * r0).into()),
+            ]);
+            SigningToken {
+                z0: r0 + c0 * sk.0,
+                c0,
+                m0,
+                r1: None,
+            }
+        })
+        .collect::<Vec<_>>();
+    let rev_key = tokens.iter().map(|token| token.m0).collect();
+    (tokens, rev_key)
+}
+
+fn bench_delegate(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+    let (sk, _) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+    let mut group = c.benchmark_group("delegate");
+    group.sample_size(10);
+    for number_of_tokens in [100, 1_000, 10_000] {
+        let deg_spec = DelegationSpec {
+            number_of_tokens,
+            bind_message_nonce: false,
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("fixed_base_table", number_of_tokens),
+            &deg_spec,
+            |b, deg_spec| {
+                b.iter(|| {
+                    AN23ProxySignature::<Projective>::delegate(&mut rng, &parameters, &sk, deg_spec)
+                        .unwrap()
+                })
+            },
+        );
+
+        // Reference: the same tokens, with double-and-add for the generator multiplications
+        group.bench_with_input(
+            BenchmarkId::new("double_and_add", number_of_tokens),
+            &number_of_tokens,
+            |b, &number_of_tokens| {
+                b.iter(|| delegate_double_and_add(&mut rng, &parameters, &sk, number_of_tokens))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_generator_mul, bench_delegate);
+criterion_main!(benches);
@@ -3,24 +3,32 @@ use ark_serialize::CanonicalSerialize;
 use rand::rngs::OsRng;
 use schnorr_tokens::ProxySignature;
 use schnorr_tokens::an23_proxy_signature::{AN23ProxySignature, DelegationSpec};
+use schnorr_tokens::wallet::DelegationBundle;
 use std::fs::File;
 use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 fn main() {
-    let mut rng = OsRng::default();
+    let mut rng = OsRng;
 
     let parameters = AN23ProxySignature::<GrumpkinCurve>::setup(&mut rng).expect("Setup failed");
 
     let (signing_key, verification_key) =
-        AN23ProxySignature::keygen(&mut rng, &parameters).expect("Key generation failed");
+        AN23ProxySignature::<GrumpkinCurve>::keygen(&mut rng, &parameters)
+            .expect("Key generation failed");
 
     let delegation_spec = DelegationSpec {
         number_of_tokens: 1,
+        bind_message_nonce: false,
     };
 
-    let (delegation_info, _) =
-        AN23ProxySignature::delegate(&mut rng, &parameters, &signing_key, &delegation_spec)
-            .expect("Delegation failed");
+    let (delegation_info, _) = AN23ProxySignature::<GrumpkinCurve>::delegate(
+        &mut rng,
+        &parameters,
+        &signing_key,
+        &delegation_spec,
+    )
+    .expect("Delegation failed");
 
     // Export parameters to a file
     let mut params_bytes = Vec::new();
@@ -38,12 +46,13 @@ fn main() {
     let mut file = File::create("examples/verification_key.bin").expect("Unable to create file");
     file.write_all(&vk_bytes).expect("Unable to write data");
 
-    // Export delegation_info to a file
-    let mut delegation_bytes = Vec::new();
-    delegation_info
-        .serialize_compressed(&mut delegation_bytes)
-        .expect("Serialization failed");
-    let mut file = File::create("examples/delegation_info.bin").expect("Unable to create file");
-    file.write_all(&delegation_bytes)
-        .expect("Unable to write data");
+    // Export delegation_info to a file, valid for 30 days
+    let expires_at = SystemTime::now() + Duration::from_secs(30 * 24 * 60 * 60);
+    let bundle = DelegationBundle {
+        tokens: delegation_info,
+        expires_at: Some(expires_at.duration_since(UNIX_EPOCH).unwrap().as_secs()),
+    };
+    bundle
+        .save("examples/delegation_info.bin")
+        .expect("Unable to write delegation bundle");
 }
@@ -3,7 +3,8 @@ use ark_grumpkin::{Fr as GrumpkinScalar, Projective as GrumpkinCurve};
 use ark_serialize::CanonicalDeserialize;
 use rand::rngs::OsRng;
 use schnorr_tokens::ProxySignature;
-use schnorr_tokens::an23_proxy_signature::{AN23ProxySignature};
+use schnorr_tokens::an23_proxy_signature::AN23ProxySignature;
+use schnorr_tokens::wallet::TokenWallet;
 use std::fs::File;
 use std::io::Read;
 
@@ -33,27 +34,23 @@ fn main() {
     let verification_key = <AN23ProxySignature<GrumpkinCurve> as ProxySignature>::VerificationKey::deserialize_compressed(&*vk_bytes)
         .expect("Deserialization failed");
 
-    // Import delegation info
-    let mut delegation_bytes = Vec::new();
-    File::open("examples/delegation_info.bin")
-        .expect("Unable to open delegation info file")
-        .read_to_end(&mut delegation_bytes)
-        .expect("Unable to read delegation info file");
-    let delegation_info = <AN23ProxySignature<GrumpkinCurve> as ProxySignature>::DelegationInfo::deserialize_compressed(&*delegation_bytes)
-        .expect("Deserialization failed");
+    // Open the delegation info. Used tokens are recorded next to it, in delegation_info.bin.spent
+    let mut wallet = TokenWallet::<GrumpkinCurve>::open("examples/delegation_info.bin")
+        .expect("Unable to open delegation info file");
+    println!(
+        "{} token(s) left, expiring at {:?}\n",
+        wallet.remaining(),
+        wallet.expires_at()
+    );
 
     println!("Generating a random message\n");
-    // Now you can use `parameters`, `verification_key`, and `delegation_info` as needed
+    // Now you can use `parameters`, `verification_key`, and `wallet` as needed
     let message = GrumpkinScalar::rand(&mut rng);
 
     println!("Signing using the delegation info\n");
-    let signature = AN23ProxySignature::<GrumpkinCurve>::delegated_sign(
-        &mut rng,
-        &parameters,
-        &mut delegation_info.clone(),
-        &message,
-    )
-    .expect("Delegated signing failed");
+    let signature = wallet
+        .sign(&mut rng, &parameters, &message)
+        .expect("Delegated signing failed");
 
     println!("{:?}\n", signature);
 
@@ -0,0 +1,60 @@
+use ark_grumpkin::Fr as GrumpkinScalar;
+use ark_serialize::CanonicalDeserialize;
+use schnorr_tokens::revocation_db::RevocationDb;
+use std::fs::File;
+use std::io::Read;
+
+const USAGE: &str = "Usage:
+    cargo run --features revocation-db --example revocation_db -- migrate <revocation_state.bin> <db>
+    cargo run --features revocation-db --example revocation_db -- compact <db>
+    cargo run --features revocation-db --example revocation_db -- stats <db>";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["migrate", state_path, db_path] => {
+            // Import a revocation state serialized with `CanonicalSerialize`
+            let mut state_bytes = Vec::new();
+            File::open(state_path)
+                .expect("Unable to open revocation state file")
+                .read_to_end(&mut state_bytes)
+                .expect("Unable to read revocation state file");
+            let rev_state = Vec::<GrumpkinScalar>::deserialize_compressed(&*state_bytes)
+                .expect("Deserialization failed");
+
+            let db = RevocationDb::open(db_path).expect("Unable to open database");
+            let inserted = db.migrate_from(&rev_state).expect("Migration failed");
+            println!(
+                "Imported {inserted} new entries ({} in the file).",
+                rev_state.len()
+            );
+        }
+        ["compact", db_path] => {
+            let mut db =
+                RevocationDb::<GrumpkinScalar>::open(db_path).expect("Unable to open database");
+            let compacted = db.compact().expect("Compaction failed");
+            println!(
+                "{}",
+                if compacted {
+                    "Database compacted."
+                } else {
+                    "Nothing to compact."
+                }
+            );
+        }
+        ["stats", db_path] => {
+            let db =
+                RevocationDb::<GrumpkinScalar>::open(db_path).expect("Unable to open database");
+            println!(
+                "{} revoked or spent tokens.",
+                db.len().expect("Unable to read database")
+            );
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
@@ -1,16 +1,39 @@
-// const Schnorr = require("./pkg/schnorr_tokens")
+// Runs the whole flow on the Node.js bindings. Build them first, see "Compiling to WASM" in the
+// README, then run `node index.js`.
 import * as Schnorr from "./pkg/schnorr_tokens.js";
-console.log("Schnorr:", Schnorr);
 
-const cfg = Schnorr.setup();
+const params = Schnorr.setup();
 
-const keypair = Schnorr.keygen(cfg);
-console.log("private key:", keypair.sk().bytes());
+const keypair = Schnorr.keygen(params);
+console.log("verification key:", keypair.vk().to_hex());
 
+const msg = Schnorr.hash_to_field(new Uint8Array([1, 2, 3, 255, 0]));
+console.log("msg:", msg.to_hex());
 
-const msg = Schnorr.hash_to_field(new Uint8Array([1, 2, 3, 255, 0]))
-console.log("msg:", msg.bytes());
-const sig = Schnorr.sign(cfg, keypair.sk(), msg, null)
+const sig = Schnorr.sign(params, keypair.sk(), msg, null);
+const revocationState = new Schnorr.RevocationState();
+console.log("signature valid:", Schnorr.verify(params, keypair.vk(), msg, sig, revocationState));
 
-const delegated_stuff = Schnorr.delegate(cfg, keypair.sk(), 1n);
-const sig_delegated = Schnorr.delegated_sign(cfg, delegated_stuff.delegation_info(), msg);
\ No newline at end of file
+// The delegator hands the tokens to a proxy and keeps the revocation key
+const delegation = Schnorr.delegate(params, keypair.sk(), 2n);
+const wallet = new Schnorr.DelegationWallet(delegation.delegation_info(), null);
+const now = BigInt(Math.floor(Date.now() / 1000));
+
+// Persist the bytes returned by `reserve` before signing, so a used token is never reused
+const persisted = wallet.reserve(now);
+console.log("wallet to persist:", persisted.length, "bytes");
+const delegatedSig = wallet.sign_reserved(params, msg, now);
+console.log(
+  "delegated signature valid:",
+  Schnorr.verify(params, keypair.vk(), msg, delegatedSig, revocationState),
+);
+console.log("tokens left:", wallet.remaining());
+
+// Revoking the delegation makes the verifier reject its remaining token
+Schnorr.revoke(delegation.revokation_key(), revocationState);
+try {
+  wallet.reserve(now);
+  Schnorr.verify(params, keypair.vk(), msg, wallet.sign_reserved(params, msg, now), revocationState);
+} catch (error) {
+  console.log("after revocation:", error.message);
+}
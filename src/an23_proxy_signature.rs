@@ -1,17 +1,29 @@
+use crate::challenge_hash::{Blake2s, ChallengeHash, ChallengeInput};
+use crate::fixed_base::{DEFAULT_WINDOW, FixedBaseTable};
+use crate::revocation_store::RevocationStore;
 use crate::{Error, ProxySignature};
 use ark_ec::CurveGroup;
-use ark_ff::{
-    BigInteger, Field, PrimeField, UniformRand,
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
 };
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use blake2::{Blake2s256, Digest};
 use rand::Rng;
-
-pub struct AN23ProxySignature<G: CurveGroup> {
-    _marker: std::marker::PhantomData<G>,
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fmt;
+use std::io::{Read, Write};
+use std::sync::{Arc, OnceLock};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// AN23 proxy signatures over `G`, with Fiat-Shamir challenges computed by `H`.
+pub struct AN23ProxySignature<G: CurveGroup, H = Blake2s> {
+    _marker: std::marker::PhantomData<(G, H)>,
 }
 
-impl<G: CurveGroup> ProxySignature for AN23ProxySignature<G>
+impl<G: CurveGroup, H: ChallengeHash<G>> ProxySignature for AN23ProxySignature<G, H>
 where
     G::ScalarField: PrimeField,
     G::BaseField: PrimeField,
@@ -29,7 +41,7 @@ where
 
     fn setup<R: rand::Rng>(_rng: &mut R) -> Result<Self::Parameters, crate::Error> {
         let generator = G::generator();
-        Ok(Parameters { generator })
+        Ok(Parameters::new(generator))
     }
 
     fn keygen<R: rand::Rng>(
@@ -37,7 +49,7 @@ where
         parameters: &Self::Parameters,
     ) -> Result<(Self::SigningKey, Self::VerificationKey), crate::Error> {
         let signing_key = G::ScalarField::rand(rng);
-        let verification_key = parameters.generator.mul(signing_key).into();
+        let verification_key = parameters.mul_generator(signing_key).into();
 
         Ok((SigningKey(signing_key), verification_key))
     }
@@ -49,8 +61,8 @@ where
         message: &Self::Message,
         _policy: Option<&Self::Policy>,
     ) -> Result<Self::Signature, crate::Error> {
-        let vk = parameters.generator.mul(sk.0).into();
-        let signing_token = Self::generate_delegation_token(rng, parameters, sk, &vk)?;
+        let vk = parameters.mul_generator(sk.0).into();
+        let signing_token = Self::generate_delegation_token(rng, parameters, sk, &vk, false)?;
 
         Self::delegated_sign(rng, parameters, &mut vec![signing_token], message)
     }
@@ -61,16 +73,31 @@ where
         sk: &Self::SigningKey,
         deg_spec: &Self::DelegationSpec,
     ) -> Result<(Self::DelegationInfo, Self::RevocationKey), crate::Error> {
-        let vk = parameters.generator.mul(sk.0).into();
-
-        let mut delegation_info = Vec::new();
-        let mut rev_key = Vec::new();
-
-        for _ in 0..deg_spec.number_of_tokens {
-            let signing_token = Self::generate_delegation_token(rng, parameters, sk, &vk)?;
-            rev_key.push(signing_token.m0); // Store m0 as revocation key
-            delegation_info.push(signing_token);
-        }
+        let vk = parameters.mul_generator(sk.0).into();
+
+        // Every token gets its own RNG seeded from the caller's RNG, so that tokens can be
+        // generated independently while matching the sequential output.
+        let seeds = (0..deg_spec.number_of_tokens)
+            .map(|_| Self::token_seed(rng))
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "parallel")]
+        let seeds = seeds.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let seeds = seeds.into_iter();
+
+        let delegation_info = seeds
+            .map(|seed| {
+                Self::generate_delegation_token(
+                    &mut ChaCha20Rng::from_seed(seed),
+                    parameters,
+                    sk,
+                    &vk,
+                    deg_spec.bind_message_nonce,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rev_key = delegation_info.iter().map(|token| token.m0).collect(); // Store m0 as revocation key
 
         Ok((delegation_info, rev_key))
     }
@@ -82,29 +109,9 @@ where
         message: &Self::Message,
     ) -> Result<Self::Signature, crate::Error> {
         let signing_token = delegation_info.pop().ok_or(Error::NoDelegationToken)?;
+        let mut presigned = Self::presign(rng, parameters, &mut vec![signing_token], 1);
 
-        // Second layer, uses z0 as signing key, signs real message m1;
-        let Z0 = parameters.generator.mul(signing_token.z0);
-        let r1 = G::ScalarField::rand(rng); // e
-        let R1 = parameters.generator.mul(r1);
-        let c1 = hash::<G>(vec![
-            Message::Field(message.clone()),
-            Message::Curve(Z0.into()),
-            Message::Curve(R1.into()),
-        ]); // c
-        let z1 = r1 + c1 * signing_token.z0; // s
-
-        let sigma = Sigma {
-            c0: signing_token.c0,
-            c1,
-            z1,
-        };
-        let theta = Theta {
-            m0: signing_token.m0,
-            Z0,
-        };
-
-        Ok(Signature { sigma, theta })
+        Self::presigned_sign(&mut presigned, message)
     }
 
     fn revoke(
@@ -124,80 +131,360 @@ where
         signature: &Self::Signature,
         rev_state: &mut Self::RevocationState,
     ) -> Result<bool, crate::Error> {
-        for &nonce in rev_state.iter() {
-            if signature.theta.m0 == nonce {
-                return Err(Error::UseOfRevokedToken); // Token is revoked
-            }
+        Self::verify_inner(parameters, vk, message, signature, rev_state, false)
+    }
+}
+
+impl<G: CurveGroup, H: ChallengeHash<G>> AN23ProxySignature<G, H>
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    fn token_seed<R: Rng>(rng: &mut R) -> <ChaCha20Rng as SeedableRng>::Seed {
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+        seed
+    }
+
+    fn generate_delegation_token<R: Rng>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        sk: &SigningKey<G>,
+        vk: &VerificationKey<G>,
+        bind_message_nonce: bool,
+    ) -> Result<SigningToken<G>, crate::Error> {
+        let (m0, r1) = if bind_message_nonce {
+            // Fix the message-layer nonce and derive the token identifier from its commitment,
+            // so that verifiers can check that the proxy used it.
+            let r1 = G::ScalarField::rand(rng);
+            let R1 = parameters.mul_generator(r1);
+            (H::hash(&[ChallengeInput::Curve(R1.into())]), Some(r1))
+        } else {
+            (G::ScalarField::rand(rng), None) // k
+        };
+        let r0 = G::ScalarField::rand(rng); // r
+        let R0 = parameters.mul_generator(r0);
+        let c0 = H::hash(&[
+            ChallengeInput::Field(m0),
+            ChallengeInput::Curve(*vk), // [x]G
+            ChallengeInput::Curve(R0.into()),
+        ]); // w
+        let z0 = r0 + c0 * sk.0; // z
+
+        Ok(SigningToken { z0, c0, m0, r1 })
+    }
+
+    /// Lazy variant of [`ProxySignature::delegate`] that yields each signing token together with
+    /// its revocation scalar, so that large delegations never have to be held in memory. For the
+    /// same RNG, the tokens are the same as those returned by `delegate`.
+    pub fn delegate_iter<'a, R: Rng>(
+        rng: &'a mut R,
+        parameters: &'a Parameters<G>,
+        sk: &'a SigningKey<G>,
+        deg_spec: &DelegationSpec,
+    ) -> impl Iterator<Item = Result<(SigningToken<G>, G::ScalarField), crate::Error>> + 'a {
+        let vk = parameters.mul_generator(sk.0).into();
+        let bind_message_nonce = deg_spec.bind_message_nonce;
+
+        (0..deg_spec.number_of_tokens).map(move |_| {
+            let signing_token = Self::generate_delegation_token(
+                &mut ChaCha20Rng::from_seed(Self::token_seed(rng)),
+                parameters,
+                sk,
+                &vk,
+                bind_message_nonce,
+            )?;
+            let rev_key = signing_token.m0;
+            Ok((signing_token, rev_key))
+        })
+    }
+
+    /// Offline half of [`ProxySignature::delegated_sign`]: takes up to `batch_size` tokens off
+    /// the delegation info and precomputes their `Z0` and message-layer nonce `(r1, R1)`.
+    /// The presigned tokens are popped in the same order as `delegated_sign` would pop them.
+    pub fn presign<R: Rng>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        delegation_info: &mut Vec<SigningToken<G>>,
+        batch_size: usize,
+    ) -> Vec<PresignedToken<G>> {
+        let start = delegation_info.len().saturating_sub(batch_size);
+        let tokens = delegation_info.split_off(start);
+
+        let mut r1s = Vec::with_capacity(tokens.len());
+        let mut points = Vec::with_capacity(2 * tokens.len());
+        for token in tokens.iter() {
+            let r1 = token.r1.unwrap_or_else(|| G::ScalarField::rand(rng)); // e
+            points.push(parameters.mul_generator(token.z0)); // Z0
+            points.push(parameters.mul_generator(r1)); // R1
+            r1s.push(r1);
+        }
+        let points = G::normalize_batch(&points);
+
+        tokens
+            .into_iter()
+            .zip(r1s)
+            .zip(points.chunks_exact(2))
+            .map(|((token, r1), points)| PresignedToken {
+                token,
+                Z0: points[0],
+                r1,
+                R1: points[1],
+            })
+            .collect()
+    }
+
+    /// Online half of [`ProxySignature::delegated_sign`]: signs with the last presigned token,
+    /// which costs a hash and a field multiply-add.
+    pub fn presigned_sign(
+        presigned: &mut Vec<PresignedToken<G>>,
+        message: &G::ScalarField,
+    ) -> Result<Signature<G>, crate::Error> {
+        let PresignedToken { token, Z0, r1, R1 } =
+            presigned.pop().ok_or(Error::NoDelegationToken)?;
+
+        // Second layer, uses z0 as signing key, signs real message m1;
+        let c1 = H::hash(&[
+            ChallengeInput::Field(*message),
+            ChallengeInput::Curve(Z0),
+            ChallengeInput::Curve(R1),
+        ]); // c
+        let z1 = r1 + c1 * token.z0; // s
+
+        let sigma = Sigma {
+            c0: token.c0,
+            c1,
+            z1,
+        };
+        let theta = Theta {
+            m0: token.m0,
+            Z0: Z0.into(),
+        };
+
+        Ok(Signature { sigma, theta })
+    }
+
+    /// Like [`ProxySignature::verify`], but additionally requires the token identifier `m0` to be
+    /// bound to the message-layer nonce, see [`DelegationSpec::bind_message_nonce`]. Signatures
+    /// produced from tokens issued without binding are rejected.
+    pub fn verify_with_bound_nonce(
+        parameters: &Parameters<G>,
+        vk: &VerificationKey<G>,
+        message: &G::ScalarField,
+        signature: &Signature<G>,
+        rev_state: &mut impl RevocationStore<G::ScalarField>,
+    ) -> Result<bool, crate::Error> {
+        Self::verify_inner(parameters, vk, message, signature, rev_state, true)
+    }
+
+    /// Like [`ProxySignature::verify`], but checks and updates any [`RevocationStore`], for
+    /// example a persistent one shared by several verifiers.
+    pub fn verify_with_store(
+        parameters: &Parameters<G>,
+        vk: &VerificationKey<G>,
+        message: &G::ScalarField,
+        signature: &Signature<G>,
+        rev_store: &mut impl RevocationStore<G::ScalarField>,
+    ) -> Result<bool, crate::Error> {
+        Self::verify_inner(parameters, vk, message, signature, rev_store, false)
+    }
+
+    /// Like [`ProxySignature::revoke`], but revokes into any [`RevocationStore`].
+    pub fn revoke_in_store(
+        rev_key: &[G::ScalarField],
+        rev_store: &mut impl RevocationStore<G::ScalarField>,
+    ) -> Result<(), crate::Error> {
+        for m0 in rev_key {
+            rev_store.insert_if_absent(m0)?;
+        }
+        Ok(())
+    }
+
+    /// Recovers the token secret `z0` from two signatures produced with the same bound-nonce
+    /// token on different messages. Anyone holding the secret can sign with the token, so
+    /// publishing it penalizes a proxy that double-spends.
+    pub fn extract_token_secret(
+        parameters: &Parameters<G>,
+        sig_a: &Signature<G>,
+        msg_a: &G::ScalarField,
+        sig_b: &Signature<G>,
+        msg_b: &G::ScalarField,
+    ) -> Result<G::ScalarField, crate::Error> {
+        if sig_a.theta.m0 != sig_b.theta.m0 || sig_a.theta.Z0 != sig_b.theta.Z0 || msg_a == msg_b {
+            return Err(Error::ExtractionFailed);
+        }
+
+        //   z1_a - z1_b = (r1 + c1_a * z0) - (r1 + c1_b * z0)
+        // =>         z0 = (z1_a - z1_b) / (c1_a - c1_b)
+        let c1_diff = sig_a.sigma.c1 - sig_b.sigma.c1;
+        let z0 =
+            (sig_a.sigma.z1 - sig_b.sigma.z1) * c1_diff.inverse().ok_or(Error::ExtractionFailed)?;
+
+        if parameters.mul_generator(z0) != sig_a.theta.Z0 {
+            return Err(Error::ExtractionFailed); // The signatures did not share a nonce
+        }
+
+        Ok(z0)
+    }
+
+    fn verify_inner(
+        parameters: &Parameters<G>,
+        vk: &VerificationKey<G>,
+        message: &G::ScalarField,
+        signature: &Signature<G>,
+        rev_state: &mut impl RevocationStore<G::ScalarField>,
+        require_bound_nonce: bool,
+    ) -> Result<bool, crate::Error> {
+        if rev_state.contains(&signature.theta.m0)? {
+            return Err(Error::UseOfRevokedToken); // Token is revoked
         }
 
         //       R0 = Z0 + [-c0]X
         // => [r0]G = [z0]G - [c0 * x] G
         // =>    z0 = r0 + c0 * x
-        let R0 = signature.theta.Z0 + vk.clone() * -signature.sigma.c0; // R
+        let R0 = signature.theta.Z0 + *vk * -signature.sigma.c0; // R
         //       R1 = [z1]G + [-c1]Z0
         // => [r1]G = [z1]G + [-c1 * z0]G
         // =>    z1 = r1 + c1 * z0
-        let R1 = parameters.generator.mul(signature.sigma.z1)
+        let R1 = parameters.mul_generator(signature.sigma.z1)
             + signature.theta.Z0.mul(-signature.sigma.c1); // E
 
         if signature.sigma.c0
-            != hash::<G>(vec![
-                Message::Field(signature.theta.m0),
-                Message::Curve(vk.clone()), // [x]G
-                Message::Curve(R0.into()),  // [r0]G
+            != H::hash(&[
+                ChallengeInput::Field(signature.theta.m0),
+                ChallengeInput::Curve(*vk),       // [x]G
+                ChallengeInput::Curve(R0.into()), // [r0]G
             ])
         {
             return Ok(false);
         }
 
         if signature.sigma.c1
-            != hash::<G>(vec![
-                Message::Field(message.clone()),
-                Message::Curve(signature.theta.Z0.into()),
-                Message::Curve(R1.into()),
+            != H::hash(&[
+                ChallengeInput::Field(*message),
+                ChallengeInput::Curve(signature.theta.Z0.into()),
+                ChallengeInput::Curve(R1.into()),
             ])
         {
             return Ok(false);
         }
 
-        rev_state.push(signature.theta.m0);
+        if require_bound_nonce && signature.theta.m0 != H::hash(&[ChallengeInput::Curve(R1.into())])
+        {
+            return Ok(false);
+        }
+
+        // Another verifier sharing the store may have accepted the same token in the meantime
+        if !rev_state.insert_if_absent(&signature.theta.m0)? {
+            return Err(Error::UseOfRevokedToken);
+        }
 
         Ok(true)
     }
 }
 
-impl<G: CurveGroup> AN23ProxySignature<G>
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct Parameters<G: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    generator: G,
+    /// Built on first use; not part of the serialized parameters.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    generator_table: OnceLock<Arc<FixedBaseTable<G>>>,
+}
+
+impl<G: CurveGroup> Parameters<G>
 where
     G::ScalarField: PrimeField,
-    G::BaseField: PrimeField,
 {
-    fn generate_delegation_token<R: Rng>(
-        rng: &mut R,
-        parameters: &Parameters<G>,
-        sk: &SigningKey<G>,
-        vk: &VerificationKey<G>,
-    ) -> Result<SigningToken<G>, crate::Error> {
-        let m0 = G::ScalarField::rand(rng); // k
-        let r0 = G::ScalarField::rand(rng); // r
-        let R0 = parameters.generator.mul(r0);
-        let c0 = hash::<G>(vec![
-            Message::Field(m0),
-            Message::Curve(vk.clone()), // [x]G
-            Message::Curve(R0.into()),
-        ]); // w
-        let z0 = r0 + c0 * sk.0; // z
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            generator_table: OnceLock::new(),
+        }
+    }
+
+    /// The generator `G` that keys are multiples of.
+    pub fn generator(&self) -> G {
+        self.generator
+    }
 
-        Ok(SigningToken { z0, c0, m0 })
+    /// Computes `[scalar]G` with a precomputed fixed-base table for the generator.
+    pub fn mul_generator(&self, scalar: G::ScalarField) -> G {
+        self.generator_table
+            .get_or_init(|| Arc::new(FixedBaseTable::new(self.generator, DEFAULT_WINDOW)))
+            .mul(&scalar)
     }
 }
 
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
-pub struct Parameters<G: CurveGroup> {
-    pub generator: G,
+impl<G: CurveGroup> CanonicalSerialize for Parameters<G> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.generator.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.generator.serialized_size(compress)
+    }
+}
+
+impl<G: CurveGroup> Valid for Parameters<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.generator.check()
+    }
 }
 
-#[derive(Clone, Default, Debug, CanonicalSerialize, CanonicalDeserialize)]
-pub struct SigningKey<G: CurveGroup>(pub G::ScalarField);
+impl<G: CurveGroup> CanonicalDeserialize for Parameters<G> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let generator = G::deserialize_with_mode(reader, compress, validate)?;
+        Ok(Self {
+            generator,
+            generator_table: OnceLock::new(),
+        })
+    }
+}
+
+/// A signing key. It is wiped from memory when dropped, and its `Debug` output is redacted.
+#[derive(Clone, Default, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct SigningKey<G: CurveGroup>(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))] pub G::ScalarField,
+);
+
+impl<G: CurveGroup> fmt::Debug for SigningKey<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SigningKey(<redacted>)")
+    }
+}
+
+impl<G: CurveGroup> Zeroize for SigningKey<G> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<G: CurveGroup> Drop for SigningKey<G> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<G: CurveGroup> ZeroizeOnDrop for SigningKey<G> {}
 
 pub type VerificationKey<G> = <G as CurveGroup>::Affine;
 
@@ -207,17 +494,134 @@ pub struct Policy {
 
 pub struct DelegationSpec {
     pub number_of_tokens: u64,
+    /// Fix the message-layer nonce of every token at issuance. A proxy that signs twice with the
+    /// same token then reveals the token secret, see [`AN23ProxySignature::extract_token_secret`].
+    pub bind_message_nonce: bool,
 }
 
 /// A token produced by the original signer and user by the proxy to produce a signature.
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// Serialized with a leading [`SIGNING_TOKEN_VERSION`] byte.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct SigningToken<G: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub z0: G::ScalarField, // z
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub c0: G::ScalarField, // w
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub m0: G::ScalarField, // k
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_utils::canonical_option")
+    )]
+    pub r1: Option<G::ScalarField>, // e, only set if the nonce is bound to the token
+}
+
+/// The version byte of serialized [`SigningToken`]s. Version 0 tokens, from before nonces could
+/// be bound, had neither the byte nor `r1`; see [`SigningToken::deserialize_v0_list`].
+pub const SIGNING_TOKEN_VERSION: u8 = 1;
+
+impl<G: CurveGroup> SigningToken<G> {
+    /// Decodes a compressed `Vec` of version 0 tokens, the delegation files written before
+    /// nonces could be bound. Serializing the result migrates such a file.
+    pub fn deserialize_v0_list(mut bytes: &[u8]) -> Result<Vec<Self>, SerializationError> {
+        let tokens =
+            Vec::<(G::ScalarField, G::ScalarField, G::ScalarField)>::deserialize_compressed(
+                &mut bytes,
+            )?;
+        if !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(tokens
+            .into_iter()
+            .map(|(z0, c0, m0)| SigningToken {
+                z0,
+                c0,
+                m0,
+                r1: None,
+            })
+            .collect())
+    }
+}
+
+impl<G: CurveGroup> CanonicalSerialize for SigningToken<G> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        SIGNING_TOKEN_VERSION.serialize_with_mode(&mut writer, compress)?;
+        self.z0.serialize_with_mode(&mut writer, compress)?;
+        self.c0.serialize_with_mode(&mut writer, compress)?;
+        self.m0.serialize_with_mode(&mut writer, compress)?;
+        self.r1.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        SIGNING_TOKEN_VERSION.serialized_size(compress)
+            + self.z0.serialized_size(compress)
+            + self.c0.serialized_size(compress)
+            + self.m0.serialized_size(compress)
+            + self.r1.serialized_size(compress)
+    }
+}
+
+impl<G: CurveGroup> Valid for SigningToken<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.z0.check()?;
+        self.c0.check()?;
+        self.m0.check()?;
+        self.r1.check()
+    }
+}
+
+impl<G: CurveGroup> CanonicalDeserialize for SigningToken<G> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        if u8::deserialize_with_mode(&mut reader, compress, validate)? != SIGNING_TOKEN_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(SigningToken {
+            z0: G::ScalarField::deserialize_with_mode(&mut reader, compress, validate)?,
+            c0: G::ScalarField::deserialize_with_mode(&mut reader, compress, validate)?,
+            m0: G::ScalarField::deserialize_with_mode(&mut reader, compress, validate)?,
+            r1: Option::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
 }
 
+/// A signing token with its message-layer values precomputed, see [`AN23ProxySignature::presign`].
+/// Holds the nonce `r1`; treat with the same care as the token itself and never sign twice with it.
 #[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct PresignedToken<G: CurveGroup> {
+    pub token: SigningToken<G>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    pub Z0: G::Affine,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    pub r1: G::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    pub R1: G::Affine,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 /// A AN23 signature. Can be produced by either the original signer or the proxy.
 pub struct Signature<G: CurveGroup> {
     pub sigma: Sigma<G::ScalarField>,
@@ -225,60 +629,39 @@ pub struct Signature<G: CurveGroup> {
 }
 
 #[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Sigma<F: Field> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub c0: F,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub c1: F,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub z1: F,
 }
 
 #[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct Theta<G: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub m0: G::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
     pub Z0: G,
 }
 
-enum Message<G: CurveGroup> {
-    Field(G::ScalarField),
-    Curve(G::Affine),
-    Bytes(Vec<u8>),
-}
-
-impl<G: CurveGroup> Message<G> {
-    fn to_bytes(&self) -> Vec<u8> {
-        match self {
-            Self::Field(value) => value.into_bigint().to_bytes_be(),
-            Self::Curve(point) => {
-                let mut uncompressed_bytes = Vec::new();
-                point
-                    .serialize_uncompressed(&mut uncompressed_bytes)
-                    .unwrap();
-
-                uncompressed_bytes
-            }
-            Self::Bytes(bytes) => bytes.clone(),
-        }
-    }
-}
-
 pub fn hash_to_field<F: PrimeField>(data: &[u8]) -> F {
     let mut hasher = Blake2s256::new();
-    let data = [0; 160];
     hasher.update(data);
     let mut out = hasher.finalize();
     out[31] = 0;
-    let out = F::from_le_bytes_mod_order(&out);
-    out
-}
-fn hash<G: CurveGroup>(message: Vec<Message<G>>) -> G::ScalarField
-where
-    G::ScalarField: PrimeField,
-    G::BaseField: PrimeField,
-{
-    let preimage = message
-        .iter()
-        .flat_map(|m| m.to_bytes())
-        .collect::<Vec<_>>();
-    hash_to_field(&preimage)
+    F::from_le_bytes_mod_order(&out)
 }
 
 #[cfg(test)]
@@ -287,8 +670,10 @@ mod tests {
     use crate::noir_utils::{
         grumpkin_fr_to_nr_code, grumpkin_point_to_nr_code, grumpkin_sig_to_nr_code,
     };
+    use ark_ec::PrimeGroup;
     use ark_grumpkin::{Fr, Projective};
     use ark_std::test_rng;
+    use rand::RngCore;
 
     #[test]
     fn test_an23_proxy_signature_vanilla() {
@@ -328,6 +713,7 @@ mod tests {
             &sk,
             &DelegationSpec {
                 number_of_tokens: 5,
+                bind_message_nonce: false,
             },
         )
         .unwrap();
@@ -369,6 +755,7 @@ mod tests {
             &sk,
             &DelegationSpec {
                 number_of_tokens: 5,
+                bind_message_nonce: false,
             },
         )
         .unwrap();
@@ -423,6 +810,7 @@ mod tests {
             &sk,
             &DelegationSpec {
                 number_of_tokens: 5,
+                bind_message_nonce: false,
             },
         )
         .unwrap();
@@ -458,6 +846,267 @@ mod tests {
         assert_eq!(verifier_decision, Err(Error::UseOfRevokedToken)); // Should fail due to revocation
     }
 
+    #[test]
+    fn test_double_spend_reveals_token_secret() {
+        let mut rng = test_rng();
+        // Extraction checks the secret against the parameters' generator, not the default one
+        let parameters = Parameters::new(Projective::generator() * Fr::from(3u64));
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let (delegation_info, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: true,
+            },
+        )
+        .unwrap();
+        let token = delegation_info[0].clone();
+
+        let msg_a = Fr::rand(&mut rng);
+        let msg_b = Fr::rand(&mut rng);
+
+        // The proxy spends the same token twice, at two offline verifiers
+        let sig_a = AN23ProxySignature::<Projective>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut delegation_info.clone(),
+            &msg_a,
+        )
+        .unwrap();
+        let sig_b = AN23ProxySignature::<Projective>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut delegation_info.clone(),
+            &msg_b,
+        )
+        .unwrap();
+
+        for (msg, sig) in [(&msg_a, &sig_a), (&msg_b, &sig_b)] {
+            assert!(
+                AN23ProxySignature::<Projective>::verify_with_bound_nonce(
+                    &parameters,
+                    &vk,
+                    msg,
+                    sig,
+                    &mut vec![],
+                )
+                .unwrap()
+            );
+        }
+
+        let z0 = AN23ProxySignature::<Projective>::extract_token_secret(
+            &parameters,
+            &sig_a,
+            &msg_a,
+            &sig_b,
+            &msg_b,
+        )
+        .unwrap();
+        assert_eq!(z0, token.z0);
+
+        // Extraction needs two different messages
+        assert_eq!(
+            AN23ProxySignature::<Projective>::extract_token_secret(
+                &parameters,
+                &sig_a,
+                &msg_a,
+                &sig_a,
+                &msg_a
+            ),
+            Err(Error::ExtractionFailed)
+        );
+    }
+
+    #[test]
+    fn test_bound_nonce_is_enforced() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let (mut delegation_info, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 2,
+                bind_message_nonce: true,
+            },
+        )
+        .unwrap();
+
+        // A proxy that discards the bound nonce produces a signature that only passes the
+        // plain verification
+        delegation_info[1].r1 = None;
+        let message = Fr::rand(&mut rng);
+        let signature = AN23ProxySignature::<Projective>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut delegation_info,
+            &message,
+        )
+        .unwrap();
+
+        assert!(
+            !AN23ProxySignature::<Projective>::verify_with_bound_nonce(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut vec![],
+            )
+            .unwrap()
+        );
+        assert!(
+            AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut vec![],
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_presigned_delegation() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let (mut delegation_info, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 5,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let last_m0 = delegation_info[4].m0;
+
+        let mut presigned = AN23ProxySignature::<Projective>::presign(
+            &mut rng,
+            &parameters,
+            &mut delegation_info,
+            3,
+        );
+        assert_eq!(presigned.len(), 3);
+        assert_eq!(delegation_info.len(), 2);
+
+        let mut rev_state = Vec::new();
+        for i in 0..3 {
+            let message = Fr::rand(&mut rng);
+            let signature =
+                AN23ProxySignature::<Projective>::presigned_sign(&mut presigned, &message).unwrap();
+            if i == 0 {
+                assert_eq!(signature.theta.m0, last_m0);
+            }
+
+            assert!(
+                AN23ProxySignature::<Projective>::verify(
+                    &parameters,
+                    &vk,
+                    &message,
+                    &signature,
+                    &mut rev_state,
+                )
+                .unwrap()
+            );
+        }
+
+        assert!(matches!(
+            AN23ProxySignature::<Projective>::presigned_sign(&mut presigned, &Fr::rand(&mut rng)),
+            Err(Error::NoDelegationToken)
+        ));
+    }
+
+    #[test]
+    fn test_delegation_matches_per_token_seeds() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+        let deg_spec = DelegationSpec {
+            number_of_tokens: 64,
+            bind_message_nonce: true,
+        };
+
+        let (delegation_info, rev_key) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng.clone(),
+            &parameters,
+            &sk,
+            &deg_spec,
+        )
+        .unwrap();
+
+        // Sequential reference, regardless of whether the `parallel` feature is enabled
+        let expected = (0..deg_spec.number_of_tokens)
+            .map(|_| {
+                let mut seed = [0u8; 32];
+                rng.fill_bytes(&mut seed);
+                AN23ProxySignature::<Projective>::generate_delegation_token(
+                    &mut ChaCha20Rng::from_seed(seed),
+                    &parameters,
+                    &sk,
+                    &vk,
+                    true,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        let mut expected_bytes = Vec::new();
+        delegation_info.serialize_compressed(&mut bytes).unwrap();
+        expected.serialize_compressed(&mut expected_bytes).unwrap();
+        assert_eq!(bytes, expected_bytes);
+        assert_eq!(
+            rev_key,
+            expected.iter().map(|token| token.m0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_v0_signing_tokens_migrate() {
+        // Two tokens written by `delegate` before `r1` and the version byte were added. That
+        // `delegate` drew `m0` then `r0` for each token straight from the RNG, after `keygen`.
+        let bytes = include_bytes!("../tests/fixtures/signing_tokens_v0.bin");
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        assert!(Vec::<SigningToken<Projective>>::deserialize_compressed(&bytes[..]).is_err());
+        assert!(
+            SigningToken::<Projective>::deserialize_v0_list(&bytes[..bytes.len() - 1]).is_err()
+        );
+        let tokens = SigningToken::<Projective>::deserialize_v0_list(bytes).unwrap();
+        assert_eq!(tokens.len(), 2);
+        for token in tokens.iter() {
+            let m0 = Fr::rand(&mut rng);
+            let r0 = Fr::rand(&mut rng);
+            assert_eq!(token.m0, m0);
+            assert_eq!(token.z0, r0 + token.c0 * sk.0);
+            assert!(token.r1.is_none());
+        }
+
+        let mut migrated = Vec::new();
+        tokens.serialize_compressed(&mut migrated).unwrap();
+        assert_eq!(migrated.len(), bytes.len() + 2 * 2);
+        let decoded =
+            Vec::<SigningToken<Projective>>::deserialize_compressed(&migrated[..]).unwrap();
+        for (decoded, token) in decoded.iter().zip(tokens.iter()) {
+            assert_eq!(
+                (decoded.z0, decoded.c0, decoded.m0),
+                (token.z0, token.c0, token.m0)
+            );
+            assert!(decoded.r1.is_none());
+        }
+    }
+
     #[test]
     fn test_grumpkin() {
         let mut rng = test_rng();
@@ -471,15 +1120,21 @@ mod tests {
         for i in 0..10 {
             let m = Fr::rand(&mut rng);
             let signature =
-                AN23ProxySignature::<Projective>::sign(&mut rng, &parameters, &sk, &m, None).unwrap();
+                AN23ProxySignature::<Projective>::sign(&mut rng, &parameters, &sk, &m, None)
+                    .unwrap();
 
             println!("TEST CASE {i}\n");
             println!("    let msg = {};", grumpkin_fr_to_nr_code(m));
             println!("{}", grumpkin_sig_to_nr_code(&signature));
 
-            let verifier_decision =
-                AN23ProxySignature::<Projective>::verify(&parameters, &vk, &m, &signature, &mut vec![])
-                    .unwrap();
+            let verifier_decision = AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &m,
+                &signature,
+                &mut vec![],
+            )
+            .unwrap();
 
             assert!(verifier_decision);
         }
@@ -0,0 +1,281 @@
+//! Calldata for the `SimpleToken` Aztec contract in `verifier/src/main.nr`.
+//!
+//! Aztec function arguments are BN254 scalar field elements, which is the base field of
+//! Grumpkin ([`ark_grumpkin::Fq`]). Grumpkin scalars do not fit in one such field element, so
+//! they are split into 128-bit halves like Noir's `EmbeddedCurveScalar::new(lo, hi)`.
+//!
+//! The contract does not take the signed message as an argument: it recomputes it from the
+//! [`TransferIntent`], so a signature authorizes one specific payment and cannot be redirected.
+
+use crate::Error;
+use crate::ProxySignature;
+use crate::an23_proxy_signature::{
+    AN23ProxySignature, Parameters, Signature, SigningToken, VerificationKey, hash_to_field,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_grumpkin::{Fq, Fr, Projective};
+use rand::Rng;
+
+/// Domain separator of transfer messages, shared with `transfer_message` in the Noir verifier.
+const TRANSFER_TAG: &[u8; 16] = b"an23-transfer-v1";
+
+/// A payment of `amount` tokens of `token_contract` to `to`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferIntent {
+    /// The recipient's `AztecAddress`.
+    pub to: Fq,
+    pub amount: u128,
+    /// Distinguishes otherwise identical transfers.
+    pub nonce: Fq,
+    /// The address of the token contract, so a signature cannot be replayed on another token.
+    pub token_contract: Fq,
+}
+
+impl TransferIntent {
+    /// The tag, `token_contract`, `to`, `amount` and `nonce`, little-endian: 128 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = TRANSFER_TAG.to_vec();
+        bytes.extend(self.token_contract.into_bigint().to_bytes_le());
+        bytes.extend(self.to.into_bigint().to_bytes_le());
+        bytes.extend(self.amount.to_le_bytes());
+        bytes.extend(self.nonce.into_bigint().to_bytes_le());
+        bytes
+    }
+
+    /// The message to sign for this transfer.
+    pub fn message(&self) -> Fr {
+        hash_to_field(&self.to_bytes())
+    }
+}
+
+impl AN23ProxySignature<Projective> {
+    /// Signs `intent` with the last token of `delegation_info`, see
+    /// [`ProxySignature::delegated_sign`].
+    pub fn delegated_sign_transfer<R: Rng>(
+        rng: &mut R,
+        parameters: &Parameters<Projective>,
+        delegation_info: &mut Vec<SigningToken<Projective>>,
+        intent: &TransferIntent,
+    ) -> Result<Signature<Projective>, Error> {
+        Self::delegated_sign(rng, parameters, delegation_info, &intent.message())
+    }
+
+    /// Verifies that `signature` authorizes exactly `intent`, see [`ProxySignature::verify`].
+    pub fn verify_transfer(
+        parameters: &Parameters<Projective>,
+        vk: &VerificationKey<Projective>,
+        intent: &TransferIntent,
+        signature: &Signature<Projective>,
+        rev_state: &mut Vec<Fr>,
+    ) -> Result<bool, Error> {
+        Self::verify(parameters, vk, &intent.message(), signature, rev_state)
+    }
+}
+
+/// The arguments of `SimpleToken::public_transfer`. The contract supplies `token_contract`.
+pub struct PublicTransfer<'a> {
+    pub intent: &'a TransferIntent,
+    pub signature: &'a Signature<Projective>,
+}
+
+impl PublicTransfer<'_> {
+    /// The arguments in the order of the contract function: `to`, `amount`, `nonce`, `c0_lo`,
+    /// `c0_hi`, `c1_lo`, `c1_hi`, `z1_lo`, `z1_hi`, `m0_lo`, `m0_hi`, `z0_x`, `z0_y`.
+    pub fn to_fields(&self) -> Result<Vec<Fq>, Error> {
+        let (z0_x, z0_y) = self
+            .signature
+            .theta
+            .Z0
+            .into_affine()
+            .xy()
+            .ok_or(Error::InvalidSignature)?; // The contract has no encoding for infinity
+
+        let mut fields = vec![
+            self.intent.to,
+            Fq::from(self.intent.amount),
+            self.intent.nonce,
+        ];
+        for scalar in [
+            self.signature.sigma.c0,
+            self.signature.sigma.c1,
+            self.signature.sigma.z1,
+            self.signature.theta.m0,
+        ] {
+            fields.extend(split_scalar(scalar));
+        }
+        fields.extend([z0_x, z0_y]);
+        Ok(fields)
+    }
+
+    /// The arguments as a JSON array of `0x`-prefixed hex strings, as accepted by aztec.js.
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(fields_to_json(&self.to_fields()?))
+    }
+}
+
+/// A JSON array of `0x`-prefixed hex strings, as accepted by aztec.js.
+pub fn fields_to_json(fields: &[Fq]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| format!("\"{}\"", field_to_hex(field)))
+        .collect::<Vec<_>>();
+    format!("[{}]", fields.join(","))
+}
+
+/// Splits a Grumpkin scalar into its low and high 128 bits.
+pub fn split_scalar(scalar: Fr) -> [Fq; 2] {
+    let limbs = scalar.into_bigint().0;
+    let lo = (limbs[0] as u128) | (limbs[1] as u128) << 64;
+    let hi = (limbs[2] as u128) | (limbs[3] as u128) << 64;
+    [Fq::from(lo), Fq::from(hi)]
+}
+
+/// Big-endian, `0x`-prefixed and zero-padded to 32 bytes, like aztec.js prints fields.
+pub fn field_to_hex(field: &Fq) -> String {
+    format!("0x{}", hex::encode(field.into_bigint().to_bytes_be()))
+}
+
+/// Parses a big-endian hex field element such as an `AztecAddress`. Values outside the field
+/// are rejected.
+pub fn field_from_hex(hex: &str) -> Result<Fq, Error> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let mut bytes = hex::decode(hex).map_err(|_| Error::SerializationFailed)?;
+    if bytes.len() > 32 {
+        return Err(Error::SerializationFailed);
+    }
+    bytes.reverse();
+    let field = Fq::from_le_bytes_mod_order(&bytes);
+    bytes.resize(32, 0);
+    if field.into_bigint().to_bytes_le() != bytes {
+        return Err(Error::SerializationFailed);
+    }
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::an23_proxy_signature::DelegationSpec;
+    use ark_std::test_rng;
+
+    fn intent() -> TransferIntent {
+        TransferIntent {
+            to: Fq::from(0x2au64),
+            amount: 10,
+            nonce: Fq::from(7u64),
+            token_contract: Fq::from(0x1234u64),
+        }
+    }
+
+    #[test]
+    fn test_transfer_message_vector() {
+        // Shared with `test_transfer_message` in verifier/src/schnorr_token.nr
+        let [lo, hi] = split_scalar(intent().message());
+        assert_eq!(lo, Fq::from(0x159d1c1f0f1e9e9135f59b487cf01e49u128));
+        assert_eq!(hi, Fq::from(0x2ff1adbd0343244fe0ee48de07915bu128));
+    }
+
+    #[test]
+    fn test_transfer_is_bound_to_intent() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let intent = intent();
+        let signature = AN23ProxySignature::delegated_sign_transfer(
+            &mut rng,
+            &parameters,
+            &mut tokens,
+            &intent,
+        )
+        .unwrap();
+
+        // Redirecting the payment, changing the amount or replaying on another token fails
+        for tampered in [
+            TransferIntent {
+                to: Fq::from(0x2bu64),
+                ..intent.clone()
+            },
+            TransferIntent {
+                amount: 11,
+                ..intent.clone()
+            },
+            TransferIntent {
+                token_contract: Fq::from(0x1235u64),
+                ..intent.clone()
+            },
+        ] {
+            assert!(
+                !AN23ProxySignature::verify_transfer(
+                    &parameters,
+                    &vk,
+                    &tampered,
+                    &signature,
+                    &mut Vec::new()
+                )
+                .unwrap()
+            );
+        }
+        assert!(
+            AN23ProxySignature::verify_transfer(
+                &parameters,
+                &vk,
+                &intent,
+                &signature,
+                &mut Vec::new()
+            )
+            .unwrap()
+        );
+
+        let call = PublicTransfer {
+            intent: &intent,
+            signature: &signature,
+        };
+        let fields = call.to_fields().unwrap();
+        assert_eq!(fields.len(), 13);
+        assert_eq!(fields[..3], [intent.to, Fq::from(10u64), intent.nonce]);
+
+        // lo + 2^128 * hi recovers each scalar, as EmbeddedCurveScalar does
+        let shift = Fq::from(u128::MAX) + Fq::from(1u64);
+        let recombine = |i: usize| {
+            Fr::from_le_bytes_mod_order(
+                &(fields[i] + shift * fields[i + 1])
+                    .into_bigint()
+                    .to_bytes_le(),
+            )
+        };
+        assert_eq!(recombine(3), signature.sigma.c0);
+        assert_eq!(recombine(9), signature.theta.m0);
+        assert_eq!(
+            (fields[11], fields[12]),
+            signature.theta.Z0.into_affine().xy().unwrap()
+        );
+
+        let json = call.to_json().unwrap();
+        assert!(json.starts_with(
+            "[\"0x000000000000000000000000000000000000000000000000000000000000002a\",\
+             \"0x000000000000000000000000000000000000000000000000000000000000000a\","
+        ));
+        assert_eq!(json.matches("\"0x").count(), 13);
+    }
+
+    #[test]
+    fn test_field_hex() {
+        let field = Fq::from(u128::MAX);
+        assert_eq!(field_from_hex(&field_to_hex(&field)).unwrap(), field);
+        assert_eq!(field_from_hex("0x2a").unwrap(), Fq::from(42u64));
+        let modulus = format!("0x{}", hex::encode(Fq::MODULUS.to_bytes_be()));
+        assert!(field_from_hex(&modulus).is_err());
+    }
+}
@@ -0,0 +1,963 @@
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_grumpkin::Projective as GrumpkinCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::SeedableRng;
+use rand::rngs::OsRng;
+use rand_chacha::ChaCha20Rng;
+use schnorr_tokens::an23_proxy_signature::{
+    AN23ProxySignature, DelegationSpec, Parameters, Signature, SigningKey, VerificationKey,
+    hash_to_field,
+};
+use schnorr_tokens::challenge_hash::{Blake2s, ChallengeHash, Poseidon2, Sha256};
+use schnorr_tokens::key_derivation::{ExtendedSigningKey, ExtendedVerificationKey, parse_path};
+use schnorr_tokens::keystore::Keystore;
+use schnorr_tokens::noir_utils::{
+    grumpkin_fr_to_nr_code, grumpkin_point_to_nr_code, grumpkin_sig_to_nr_code, noir_test_module,
+    noir_vectors, prover_toml,
+};
+use schnorr_tokens::wallet::{DelegationBundle, TokenWallet, write_durably, write_durably_private};
+use schnorr_tokens::{Error, NamedCurve, ProxySignature};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exit codes, in addition to 0 for success and 2 for invalid usage.
+const EXIT_INVALID_SIGNATURE: u8 = 1;
+const EXIT_REVOKED: u8 = 3;
+const EXIT_IO: u8 = 4;
+const EXIT_DECODING: u8 = 5;
+const EXIT_NO_TOKEN: u8 = 6;
+const EXIT_FAILURE: u8 = 7;
+const EXIT_INVALID_PASSWORD: u8 = 8;
+
+const PASSWORD_VAR: &str = "SCHNORR_TOKENS_PASSWORD";
+
+/// Anonymous proxy signatures over Grumpkin, or the curve chosen with `--curve`.
+///
+/// Inputs and outputs are files; `-` (the default for outputs) is stdin/stdout.
+#[derive(Parser)]
+#[command(name = "schnorr-tokens", version)]
+struct Cli {
+    /// Encoding of every input and output.
+    #[arg(long, global = true, value_enum, default_value_t = Encoding::Hex)]
+    encoding: Encoding,
+
+    /// Curve of every key, token and signature. Other curves than Grumpkin need the cargo
+    /// feature of the same name (`pasta` for Pallas and Vesta).
+    #[arg(long, global = true, value_enum, default_value_t = CurveName::Grumpkin)]
+    curve: CurveName,
+
+    /// Hash of the Fiat-Shamir challenges. Delegations, signatures and verification must all
+    /// use the same one.
+    #[arg(long, global = true, value_enum, default_value_t = HashName::Blake2s)]
+    hash: HashName,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Encoding {
+    /// Compressed canonical serialization.
+    Binary,
+    /// Hex of the binary encoding.
+    Hex,
+    /// JSON object with the type and the hex of the binary encoding.
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CurveName {
+    Grumpkin,
+    #[cfg(feature = "secp256k1")]
+    Secp256k1,
+    #[cfg(feature = "bn254")]
+    Bn254,
+    #[cfg(feature = "pasta")]
+    Pallas,
+    #[cfg(feature = "pasta")]
+    Vesta,
+    #[cfg(feature = "ristretto")]
+    Ristretto,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashName {
+    /// Checked by `verify` in the Noir verifier.
+    Blake2s,
+    /// Checked by the Solidity verifiers of secp256k1 and BN254.
+    Sha256,
+    /// Checked by `verify_poseidon2` in the Noir verifier. Grumpkin only.
+    Poseidon2,
+}
+
+/// A curve of `--curve`.
+trait Curve: CurveGroup<BaseField: PrimeField> + NamedCurve {
+    /// The signature, its message and the verification key as Noir code. The Noir verifier
+    /// only supports Grumpkin.
+    fn export_noir(
+        _vk: &VerificationKey<Self>,
+        _signature: &Signature<Self>,
+        _message: &Self::ScalarField,
+    ) -> Result<String, CliError> {
+        Err(CliError::new(
+            2,
+            format!("the Noir verifier does not support {}", Self::NAME),
+        ))
+    }
+}
+
+impl Curve for GrumpkinCurve {
+    fn export_noir(
+        vk: &VerificationKey<Self>,
+        signature: &Signature<Self>,
+        message: &Self::ScalarField,
+    ) -> Result<String, CliError> {
+        Ok(format!(
+            "    let vk = {};\n    let msg = {};\n{}",
+            grumpkin_point_to_nr_code((*vk).into()),
+            grumpkin_fr_to_nr_code(*message),
+            grumpkin_sig_to_nr_code(signature)
+        ))
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl Curve for schnorr_tokens::curves::secp256k1::Projective {}
+
+#[cfg(feature = "bn254")]
+impl Curve for schnorr_tokens::curves::Bn254 {}
+
+#[cfg(feature = "pasta")]
+impl Curve for schnorr_tokens::curves::pasta::pallas::Projective {}
+
+#[cfg(feature = "pasta")]
+impl Curve for schnorr_tokens::curves::pasta::vesta::Projective {}
+
+#[cfg(feature = "ristretto")]
+impl Curve for schnorr_tokens::curves::ristretto::Projective {}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate public parameters.
+    Setup {
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Generate a signing key and print the verification key.
+    Keygen {
+        #[arg(long)]
+        params: PathBuf,
+        /// Where to write the signing key.
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Delegate signing tokens and write the delegation bundle for the proxy.
+    ///
+    /// The bundle is always written in binary encoding, as expected by `delegated-sign`.
+    Delegate {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+        /// Number of signing tokens.
+        #[arg(long)]
+        tokens: u64,
+        /// Fix the message-layer nonce of each token, so that double-spending reveals the token.
+        #[arg(long)]
+        bind_nonce: bool,
+        /// Unix time in seconds from which the proxy must stop signing.
+        #[arg(long)]
+        expires_at: Option<u64>,
+        /// Where to write the revocation key.
+        #[arg(long)]
+        revocation_key: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Sign with the signing key.
+    Sign {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+        #[command(flatten)]
+        message: MessageArgs,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Sign with the next unused token of a delegation bundle.
+    ///
+    /// The used token is recorded in `<bundle>.spent` before the signature is written.
+    DelegatedSign {
+        #[arg(long)]
+        params: PathBuf,
+        /// Delegation bundle, always in binary encoding.
+        #[arg(long)]
+        bundle: PathBuf,
+        #[command(flatten)]
+        message: MessageArgs,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Verify a signature. Exits with 1 if it is invalid and 3 if the token was revoked or spent.
+    Verify {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        verification_key: PathBuf,
+        #[arg(long)]
+        signature: PathBuf,
+        #[command(flatten)]
+        message: MessageArgs,
+        /// Revocation state, created if missing and updated on success.
+        #[arg(long)]
+        revocation_state: Option<PathBuf>,
+    },
+    /// Add a revocation key to a revocation state.
+    Revoke {
+        #[arg(long)]
+        revocation_key: PathBuf,
+        /// Revocation state, created if missing.
+        #[arg(long)]
+        revocation_state: PathBuf,
+    },
+    /// Print the mnemonic backup of a signing key.
+    ExportMnemonic {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+    },
+    /// Restore a signing key from a mnemonic and print the verification key.
+    ImportMnemonic {
+        #[arg(long)]
+        params: PathBuf,
+        /// File with the mnemonic.
+        #[arg(long, default_value = "-")]
+        mnemonic: PathBuf,
+        /// Where to write the signing key.
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Derive a child signing key, e.g. along `m/1'/0`, and print its verification key.
+    Derive {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        secret_key: PathBuf,
+        /// The secret key is a password-encrypted keystore. The password is read from the
+        /// SCHNORR_TOKENS_PASSWORD environment variable.
+        #[arg(long)]
+        keystore: bool,
+        #[arg(long)]
+        path: String,
+        /// Where to write the child signing key, in the same form as the parent.
+        #[arg(long)]
+        child_secret_key: PathBuf,
+        /// Print the extended verification key, from which non-hardened children can be derived.
+        #[arg(long)]
+        extended: bool,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Derive the verification key of a non-hardened child from an extended verification key.
+    DerivePublic {
+        #[arg(long)]
+        params: PathBuf,
+        #[arg(long)]
+        extended_verification_key: PathBuf,
+        #[arg(long)]
+        path: String,
+        /// Print the extended verification key of the child.
+        #[arg(long)]
+        extended: bool,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Print a human-readable description of an encoded value.
+    Inspect {
+        #[arg(value_enum)]
+        kind: Kind,
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+    /// Print a signature, its message and the verification key as Noir code.
+    ExportNoir {
+        #[arg(long)]
+        verification_key: PathBuf,
+        #[arg(long)]
+        signature: PathBuf,
+        #[command(flatten)]
+        message: MessageArgs,
+    },
+    /// Write a Noir test module for random, edge-case and invalid signatures.
+    GenerateNoirTests {
+        /// Number of random valid signatures, besides the edge cases and invalid ones.
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        /// Seed of the random signatures, so that regenerating gives the same tests.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Where to write one `<test name>.toml` prover input per signature, e.g. the circuit/
+        /// package to run `nargo execute -p <test name>` there.
+        #[arg(long)]
+        prover_dir: Option<PathBuf>,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+}
+
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+struct MessageArgs {
+    /// Message scalar, as hex of its little-endian bytes.
+    #[arg(long)]
+    message: Option<String>,
+    /// Message text, hashed to a scalar.
+    #[arg(long)]
+    message_text: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Kind {
+    Parameters,
+    SecretKey,
+    Keystore,
+    VerificationKey,
+    ExtendedVerificationKey,
+    Delegation,
+    RevocationKey,
+    RevocationState,
+    Signature,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Parameters => "parameters",
+            Kind::SecretKey => "secret-key",
+            Kind::Keystore => "keystore",
+            Kind::VerificationKey => "verification-key",
+            Kind::ExtendedVerificationKey => "extended-verification-key",
+            Kind::Delegation => "delegation",
+            Kind::RevocationKey => "revocation-key",
+            Kind::RevocationState => "revocation-state",
+            Kind::Signature => "signature",
+        }
+    }
+
+    /// Whether files of this kind must only be readable by their owner.
+    fn is_secret(self) -> bool {
+        matches!(self, Kind::SecretKey | Kind::Keystore | Kind::Delegation)
+    }
+}
+
+#[derive(Debug)]
+struct CliError {
+    code: u8,
+    message: String,
+}
+
+impl CliError {
+    fn new(code: u8, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for CliError {
+    fn from(error: Error) -> Self {
+        let code = match error {
+            Error::UseOfRevokedToken => EXIT_REVOKED,
+            Error::NoDelegationToken | Error::DelegationExpired => EXIT_NO_TOKEN,
+            Error::StorageFailed => EXIT_IO,
+            Error::SerializationFailed | Error::InvalidKeystore => EXIT_DECODING,
+            Error::InvalidPassword => EXIT_INVALID_PASSWORD,
+            Error::InvalidMnemonic => EXIT_DECODING,
+            _ => EXIT_FAILURE,
+        };
+        Self::new(code, format!("{error:?}"))
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error.message);
+            ExitCode::from(error.code)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), CliError> {
+    match cli.curve {
+        CurveName::Grumpkin => match cli.hash {
+            HashName::Poseidon2 => run_on::<GrumpkinCurve, Poseidon2>(cli),
+            _ => run_with_hash::<GrumpkinCurve>(cli),
+        },
+        #[cfg(feature = "secp256k1")]
+        CurveName::Secp256k1 => run_with_hash::<schnorr_tokens::curves::secp256k1::Projective>(cli),
+        #[cfg(feature = "bn254")]
+        CurveName::Bn254 => run_with_hash::<schnorr_tokens::curves::Bn254>(cli),
+        #[cfg(feature = "pasta")]
+        CurveName::Pallas => {
+            run_with_hash::<schnorr_tokens::curves::pasta::pallas::Projective>(cli)
+        }
+        #[cfg(feature = "pasta")]
+        CurveName::Vesta => run_with_hash::<schnorr_tokens::curves::pasta::vesta::Projective>(cli),
+        #[cfg(feature = "ristretto")]
+        CurveName::Ristretto => run_with_hash::<schnorr_tokens::curves::ristretto::Projective>(cli),
+    }
+}
+
+/// Runs on `G` with a hash of `--hash` that hashes over any curve.
+fn run_with_hash<G: Curve>(cli: Cli) -> Result<(), CliError> {
+    match cli.hash {
+        HashName::Blake2s => run_on::<G, Blake2s>(cli),
+        HashName::Sha256 => run_on::<G, Sha256>(cli),
+        HashName::Poseidon2 => Err(CliError::new(
+            2,
+            format!("poseidon2 does not hash over {}", G::NAME),
+        )),
+    }
+}
+
+fn run_on<G: Curve, H: ChallengeHash<G>>(cli: Cli) -> Result<(), CliError> {
+    type Scheme<G, H> = AN23ProxySignature<G, H>;
+    let encoding = cli.encoding;
+    let mut rng = OsRng;
+
+    match cli.command {
+        Command::Setup { output } => {
+            let parameters = Scheme::<G, H>::setup(&mut rng)?;
+            write_value(&output, encoding, Kind::Parameters, &parameters)
+        }
+        Command::Keygen {
+            params,
+            secret_key,
+            keystore,
+            output,
+        } => {
+            let parameters: Parameters<G> = read_value(&params, encoding, Kind::Parameters)?;
+            let (sk, vk) = Scheme::<G, H>::keygen(&mut rng, &parameters)?;
+            write_signing_key(&secret_key, encoding, keystore, &parameters, &sk)?;
+            write_value(&output, encoding, Kind::VerificationKey, &vk)
+        }
+        Command::Delegate {
+            params,
+            secret_key,
+            keystore,
+            tokens,
+            bind_nonce,
+            expires_at,
+            revocation_key,
+            output,
+        } => {
+            let parameters = read_value(&params, encoding, Kind::Parameters)?;
+            let sk = read_signing_key(&secret_key, encoding, keystore, &parameters)?;
+            let deg_spec = DelegationSpec {
+                number_of_tokens: tokens,
+                bind_message_nonce: bind_nonce,
+            };
+            let (tokens, rev_key) =
+                Scheme::<G, H>::delegate(&mut rng, &parameters, &sk, &deg_spec)?;
+            write_value(&revocation_key, encoding, Kind::RevocationKey, &rev_key)?;
+            write_value(
+                &output,
+                Encoding::Binary,
+                Kind::Delegation,
+                &DelegationBundle { tokens, expires_at },
+            )
+        }
+        Command::Sign {
+            params,
+            secret_key,
+            keystore,
+            message,
+            output,
+        } => {
+            let parameters = read_value(&params, encoding, Kind::Parameters)?;
+            let sk = read_signing_key(&secret_key, encoding, keystore, &parameters)?;
+            let message = message.scalar()?;
+            let signature = Scheme::<G, H>::sign(&mut rng, &parameters, &sk, &message, None)?;
+            write_value(&output, encoding, Kind::Signature, &signature)
+        }
+        Command::DelegatedSign {
+            params,
+            bundle,
+            message,
+            output,
+        } => {
+            let parameters = read_value(&params, encoding, Kind::Parameters)?;
+            let message = message.scalar()?;
+            let mut wallet = TokenWallet::<G, H>::open(&bundle)?;
+            let signature = wallet.sign(&mut rng, &parameters, &message)?;
+            write_value(&output, encoding, Kind::Signature, &signature)?;
+            eprintln!("{} token(s) left", wallet.remaining());
+            Ok(())
+        }
+        Command::Verify {
+            params,
+            verification_key,
+            signature,
+            message,
+            revocation_state,
+        } => {
+            let parameters = read_value(&params, encoding, Kind::Parameters)?;
+            let vk: VerificationKey<G> =
+                read_value(&verification_key, encoding, Kind::VerificationKey)?;
+            let signature: Signature<G> = read_value(&signature, encoding, Kind::Signature)?;
+            let message = message.scalar()?;
+
+            // Held until the updated state is written, so a token is never accepted twice
+            let _lock = match &revocation_state {
+                Some(path) => lock_revocation_state(path)?,
+                None => None,
+            };
+            let mut rev_state = match &revocation_state {
+                Some(path) => read_revocation_state(path, encoding)?,
+                None => Vec::new(),
+            };
+            if !Scheme::<G, H>::verify(&parameters, &vk, &message, &signature, &mut rev_state)? {
+                return Err(CliError::new(EXIT_INVALID_SIGNATURE, "invalid signature"));
+            }
+            if let Some(path) = &revocation_state {
+                write_value(path, encoding, Kind::RevocationState, &rev_state)?;
+            }
+            println!("valid");
+            Ok(())
+        }
+        Command::Revoke {
+            revocation_key,
+            revocation_state,
+        } => {
+            let rev_key: Vec<G::ScalarField> =
+                read_value(&revocation_key, encoding, Kind::RevocationKey)?;
+            let _lock = lock_revocation_state(&revocation_state)?;
+            let mut rev_state = read_revocation_state(&revocation_state, encoding)?;
+            Scheme::<G, H>::revoke_in_store(&rev_key, &mut rev_state)?;
+            write_value(
+                &revocation_state,
+                encoding,
+                Kind::RevocationState,
+                &rev_state,
+            )
+        }
+        Command::ExportMnemonic {
+            params,
+            secret_key,
+            keystore,
+        } => {
+            let parameters: Parameters<G> = read_value(&params, encoding, Kind::Parameters)?;
+            let sk = read_signing_key(&secret_key, encoding, keystore, &parameters)?;
+            println!("{}", sk.to_mnemonic()?);
+            Ok(())
+        }
+        Command::ImportMnemonic {
+            params,
+            mnemonic,
+            secret_key,
+            keystore,
+            output,
+        } => {
+            let parameters: Parameters<G> = read_value(&params, encoding, Kind::Parameters)?;
+            let phrase = String::from_utf8_lossy(&read_input(&mnemonic)?).into_owned();
+            let sk = SigningKey::<G>::from_mnemonic(phrase.trim())?;
+            write_signing_key(&secret_key, encoding, keystore, &parameters, &sk)?;
+            let vk: VerificationKey<G> = parameters.mul_generator(sk.0).into();
+            write_value(&output, encoding, Kind::VerificationKey, &vk)
+        }
+        Command::Derive {
+            params,
+            secret_key,
+            keystore,
+            path,
+            child_secret_key,
+            extended,
+            output,
+        } => {
+            let parameters: Parameters<G> = read_value(&params, encoding, Kind::Parameters)?;
+            let sk = read_signing_key(&secret_key, encoding, keystore, &parameters)?;
+            let child =
+                ExtendedSigningKey::new(sk).derive_path(&parameters, &derivation_path(&path)?)?;
+            write_signing_key(
+                &child_secret_key,
+                encoding,
+                keystore,
+                &parameters,
+                &child.signing_key,
+            )?;
+            write_verification_key(
+                &output,
+                encoding,
+                extended,
+                &child.extended_verification_key(&parameters),
+            )
+        }
+        Command::DerivePublic {
+            params,
+            extended_verification_key,
+            path,
+            extended,
+            output,
+        } => {
+            let parameters = read_value(&params, encoding, Kind::Parameters)?;
+            let xvk: ExtendedVerificationKey<G> = read_value(
+                &extended_verification_key,
+                encoding,
+                Kind::ExtendedVerificationKey,
+            )?;
+            let child = xvk.derive_path(&parameters, &derivation_path(&path)?)?;
+            write_verification_key(&output, encoding, extended, &child)
+        }
+        Command::Inspect { kind, input } => {
+            let description = match kind {
+                Kind::Parameters => {
+                    let parameters: Parameters<G> = read_value(&input, encoding, kind)?;
+                    format!("generator: {}", parameters.generator())
+                }
+                Kind::SecretKey => {
+                    let _: SigningKey<G> = read_value(&input, encoding, kind)?;
+                    "valid signing key (not shown)".to_string()
+                }
+                Kind::Keystore => {
+                    let keystore: Keystore = read_value(&input, encoding, kind)?;
+                    format!(
+                        "curve: {}\nverification key: {}\ncreated at: {}\nkdf: {:?}",
+                        keystore.curve(),
+                        keystore.verification_key::<G>()?,
+                        keystore.created_at(),
+                        keystore.kdf_params()
+                    )
+                }
+                Kind::VerificationKey => {
+                    let vk: VerificationKey<G> = read_value(&input, encoding, kind)?;
+                    format!("verification key: {vk}")
+                }
+                Kind::ExtendedVerificationKey => {
+                    let xvk: ExtendedVerificationKey<G> = read_value(&input, encoding, kind)?;
+                    format!("verification key: {}", xvk.verification_key)
+                }
+                Kind::Delegation => {
+                    let bundle: DelegationBundle<G> = read_value(&input, Encoding::Binary, kind)?;
+                    let bound = bundle
+                        .tokens
+                        .iter()
+                        .filter(|token| token.r1.is_some())
+                        .count();
+                    format!(
+                        "tokens: {}\nbound nonces: {bound}\nexpires at: {}",
+                        bundle.tokens.len(),
+                        bundle
+                            .expires_at
+                            .map_or("never".to_string(), |t| t.to_string())
+                    )
+                }
+                Kind::RevocationKey | Kind::RevocationState => {
+                    let entries: Vec<G::ScalarField> = read_value(&input, encoding, kind)?;
+                    let mut description = format!("entries: {}", entries.len());
+                    for m0 in entries {
+                        description.push_str(&format!("\n  {}", scalar_to_hex(&m0)));
+                    }
+                    description
+                }
+                Kind::Signature => {
+                    let signature: Signature<G> = read_value(&input, encoding, kind)?;
+                    format!(
+                        "c0: {}\nc1: {}\nz1: {}\nm0: {}\nZ0: {}",
+                        scalar_to_hex(&signature.sigma.c0),
+                        scalar_to_hex(&signature.sigma.c1),
+                        scalar_to_hex(&signature.sigma.z1),
+                        scalar_to_hex(&signature.theta.m0),
+                        signature.theta.Z0
+                    )
+                }
+            };
+            println!("{description}");
+            Ok(())
+        }
+        Command::ExportNoir {
+            verification_key,
+            signature,
+            message,
+        } => {
+            let vk: VerificationKey<G> =
+                read_value(&verification_key, encoding, Kind::VerificationKey)?;
+            let signature = read_value(&signature, encoding, Kind::Signature)?;
+            let message = message.scalar()?;
+            print!("{}", G::export_noir(&vk, &signature, &message)?);
+            Ok(())
+        }
+        Command::GenerateNoirTests {
+            count,
+            seed,
+            prover_dir,
+            output,
+        } => {
+            let vectors = noir_vectors(&mut ChaCha20Rng::seed_from_u64(seed), count)?;
+            if let Some(dir) = prover_dir {
+                fs::create_dir_all(&dir)
+                    .map_err(|e| CliError::new(EXIT_IO, format!("{}: {e}", dir.display())))?;
+                for vector in &vectors {
+                    let path = dir.join(format!("{}.toml", vector.name));
+                    write_output(&path, prover_toml(vector).as_bytes())?;
+                }
+            }
+            let arguments = format!("--count {count} --seed {seed}");
+            write_output(&output, noir_test_module(&vectors, &arguments).as_bytes())
+        }
+    }
+}
+
+impl MessageArgs {
+    fn scalar<F: PrimeField>(&self) -> Result<F, CliError> {
+        match (&self.message, &self.message_text) {
+            (Some(hex), _) => {
+                let bytes = decode_hex(hex)?;
+                F::deserialize_compressed(&*bytes)
+                    .map_err(|e| CliError::new(EXIT_DECODING, format!("invalid message: {e}")))
+            }
+            (None, Some(text)) => Ok(hash_to_field(text.as_bytes())),
+            (None, None) => Err(CliError::new(2, "missing message")),
+        }
+    }
+}
+
+fn scalar_to_hex<F: PrimeField>(scalar: &F) -> String {
+    let mut bytes = Vec::new();
+    scalar.serialize_compressed(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, CliError> {
+    let hex = hex.trim();
+    hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+        .map_err(|e| CliError::new(EXIT_DECODING, format!("invalid hex: {e}")))
+}
+
+fn read_input(path: &Path) -> Result<Vec<u8>, CliError> {
+    let mut bytes = Vec::new();
+    let result = if path == Path::new("-") {
+        io::stdin().read_to_end(&mut bytes).map(|_| ())
+    } else {
+        fs::File::open(path).and_then(|mut file| file.read_to_end(&mut bytes).map(|_| ()))
+    };
+    result.map_err(|e| CliError::new(EXIT_IO, format!("{}: {e}", path.display())))?;
+    Ok(bytes)
+}
+
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), CliError> {
+    let result = if path == Path::new("-") {
+        io::stdout().write_all(bytes)
+    } else {
+        fs::write(path, bytes)
+    };
+    result.map_err(|e| CliError::new(EXIT_IO, format!("{}: {e}", path.display())))
+}
+
+/// Like [`write_output`], but a file is replaced atomically by a new one that is readable and
+/// writable by its owner only.
+fn write_secret_output(path: &Path, bytes: &[u8]) -> Result<(), CliError> {
+    if path == Path::new("-") {
+        return write_output(path, bytes);
+    }
+    write_durably_private(path, bytes)
+        .map_err(|e| CliError::new(EXIT_IO, format!("{}: {e:?}", path.display())))
+}
+
+/// Like [`write_output`], but a file is replaced atomically, so that a crash never leaves a
+/// truncated file behind.
+fn write_output_atomically(path: &Path, bytes: &[u8]) -> Result<(), CliError> {
+    if path == Path::new("-") {
+        return write_output(path, bytes);
+    }
+    write_durably(path, bytes)
+        .map_err(|e| CliError::new(EXIT_IO, format!("{}: {e:?}", path.display())))
+}
+
+fn read_value<T: CanonicalDeserialize>(
+    path: &Path,
+    encoding: Encoding,
+    kind: Kind,
+) -> Result<T, CliError> {
+    let input = read_input(path)?;
+    let bytes = match encoding {
+        Encoding::Binary => input,
+        Encoding::Hex => decode_hex(&String::from_utf8_lossy(&input))?,
+        Encoding::Json => {
+            let json: serde_json::Value = serde_json::from_slice(&input)
+                .map_err(|e| CliError::new(EXIT_DECODING, format!("invalid JSON: {e}")))?;
+            if json["type"] != kind.name() {
+                return Err(CliError::new(
+                    EXIT_DECODING,
+                    format!("expected a {} in {}", kind.name(), path.display()),
+                ));
+            }
+            let data = json["data"]
+                .as_str()
+                .ok_or_else(|| CliError::new(EXIT_DECODING, "missing data field"))?;
+            decode_hex(data)?
+        }
+    };
+    T::deserialize_compressed(&*bytes).map_err(|e| {
+        CliError::new(
+            EXIT_DECODING,
+            format!("invalid {} in {}: {e}", kind.name(), path.display()),
+        )
+    })
+}
+
+fn write_value<T: CanonicalSerialize>(
+    path: &Path,
+    encoding: Encoding,
+    kind: Kind,
+    value: &T,
+) -> Result<(), CliError> {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .map_err(|e| CliError::new(EXIT_FAILURE, format!("serialization failed: {e}")))?;
+    let output = match encoding {
+        Encoding::Binary => bytes,
+        Encoding::Hex => format!("{}\n", hex::encode(bytes)).into_bytes(),
+        Encoding::Json => format!(
+            "{}\n",
+            serde_json::json!({ "type": kind.name(), "data": hex::encode(bytes) })
+        )
+        .into_bytes(),
+    };
+    match kind {
+        _ if kind.is_secret() => write_secret_output(path, &output),
+        Kind::RevocationState => write_output_atomically(path, &output),
+        _ => write_output(path, &output),
+    }
+}
+
+fn read_signing_key<G: Curve>(
+    path: &Path,
+    encoding: Encoding,
+    keystore: bool,
+    parameters: &Parameters<G>,
+) -> Result<SigningKey<G>, CliError> {
+    if keystore {
+        let keystore: Keystore = read_value(path, encoding, Kind::Keystore)?;
+        Ok(keystore.decrypt(parameters, password()?.as_bytes())?)
+    } else {
+        read_value(path, encoding, Kind::SecretKey)
+    }
+}
+
+/// Writes `sk` in plain or, with `keystore`, encrypted under the password.
+fn write_signing_key<G: Curve>(
+    path: &Path,
+    encoding: Encoding,
+    keystore: bool,
+    parameters: &Parameters<G>,
+    sk: &SigningKey<G>,
+) -> Result<(), CliError> {
+    if keystore {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+        let keystore = Keystore::encrypt(
+            &mut OsRng,
+            parameters,
+            sk,
+            password()?.as_bytes(),
+            created_at,
+        )?;
+        write_value(path, encoding, Kind::Keystore, &keystore)
+    } else {
+        write_value(path, encoding, Kind::SecretKey, sk)
+    }
+}
+
+fn write_verification_key<G: Curve>(
+    path: &Path,
+    encoding: Encoding,
+    extended: bool,
+    xvk: &ExtendedVerificationKey<G>,
+) -> Result<(), CliError> {
+    if extended {
+        write_value(path, encoding, Kind::ExtendedVerificationKey, xvk)
+    } else {
+        write_value(path, encoding, Kind::VerificationKey, &xvk.verification_key)
+    }
+}
+
+fn derivation_path(path: &str) -> Result<Vec<u32>, CliError> {
+    parse_path(path).map_err(|_| CliError::new(2, format!("invalid derivation path: {path}")))
+}
+
+fn password() -> Result<String, CliError> {
+    std::env::var(PASSWORD_VAR).map_err(|_| CliError::new(2, format!("{PASSWORD_VAR} is not set")))
+}
+
+fn read_revocation_state<F: PrimeField>(
+    path: &Path,
+    encoding: Encoding,
+) -> Result<Vec<F>, CliError> {
+    if path.exists() {
+        read_value(path, encoding, Kind::RevocationState)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Exclusively locks `<path>.lock`, like [`TokenWallet::open`] locks its bundle, until the
+/// returned file is dropped. The state file itself is replaced on every write, so it cannot hold
+/// the lock.
+fn lock_revocation_state(path: &Path) -> Result<Option<fs::File>, CliError> {
+    if path == Path::new("-") {
+        return Ok(None);
+    }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+    let lock = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| CliError::new(EXIT_IO, format!("{}: {e}", lock_path.display())))?;
+    lock.try_lock().map_err(|_| {
+        CliError::new(
+            EXIT_IO,
+            format!("{} is in use by another command", path.display()),
+        )
+    })?;
+    Ok(Some(lock))
+}
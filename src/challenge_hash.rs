@@ -0,0 +1,188 @@
+//! The hash functions behind the Fiat-Shamir challenges of
+//! [`AN23ProxySignature`](crate::an23_proxy_signature::AN23ProxySignature).
+//!
+//! [`Blake2s`] is the default and matches `hash_fcc` in the Noir verifier. [`Poseidon2`] is much
+//! cheaper in circuits and matches Noir's `std::hash::poseidon2`, but only hashes over Grumpkin,
+//! whose base field is BN254's scalar field.
+
+use crate::an23_proxy_signature::hash_to_field;
+use crate::aztec::split_scalar;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use sha2::Digest;
+
+/// An input to a challenge: a scalar, a point or raw bytes.
+pub enum ChallengeInput<G: CurveGroup> {
+    Field(G::ScalarField),
+    Curve(G::Affine),
+    Bytes(Vec<u8>),
+}
+
+impl<G: CurveGroup> ChallengeInput<G>
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    /// Little-endian encoding, matching `hash_fcc` in the Noir verifier.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Field(value) => value.into_bigint().to_bytes_le(),
+            Self::Curve(point) => {
+                let (x, y) = point
+                    .xy()
+                    .unwrap_or((G::BaseField::zero(), G::BaseField::zero()));
+                let mut bytes = x.into_bigint().to_bytes_le();
+                bytes.extend(y.into_bigint().to_bytes_le());
+
+                bytes
+            }
+            Self::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Hashes challenge inputs to a scalar.
+pub trait ChallengeHash<G: CurveGroup> {
+    fn hash(inputs: &[ChallengeInput<G>]) -> G::ScalarField;
+}
+
+fn preimage<G: CurveGroup>(inputs: &[ChallengeInput<G>]) -> Vec<u8>
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    inputs.iter().flat_map(|input| input.to_bytes()).collect()
+}
+
+/// Blake2s over the concatenated inputs, see [`hash_to_field`].
+pub struct Blake2s;
+
+impl<G: CurveGroup> ChallengeHash<G> for Blake2s
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    fn hash(inputs: &[ChallengeInput<G>]) -> G::ScalarField {
+        hash_to_field(&preimage(inputs))
+    }
+}
+
+/// SHA-256 over the concatenated inputs, with the top byte of the digest cleared like
+/// [`hash_to_field`].
+pub struct Sha256;
+
+impl<G: CurveGroup> ChallengeHash<G> for Sha256
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    fn hash(inputs: &[ChallengeInput<G>]) -> G::ScalarField {
+        let mut out = sha2::Sha256::digest(preimage(inputs));
+        out[31] = 0;
+        G::ScalarField::from_le_bytes_mod_order(&out)
+    }
+}
+
+/// [`crate::poseidon2::hash`] over the field elements of the inputs, see [`field_elements`].
+pub struct Poseidon2;
+
+impl ChallengeHash<ark_grumpkin::Projective> for Poseidon2 {
+    fn hash(inputs: &[ChallengeInput<ark_grumpkin::Projective>]) -> ark_grumpkin::Fr {
+        to_scalar(crate::poseidon2::hash(&field_elements(inputs)))
+    }
+}
+
+/// Grumpkin inputs as BN254 field elements: scalars as their low and high 128 bits, like Noir's
+/// `EmbeddedCurveScalar`, points as their coordinates with `(0, 0)` for the point at infinity, and
+/// bytes in little-endian chunks of 31.
+pub fn field_elements(
+    inputs: &[ChallengeInput<ark_grumpkin::Projective>],
+) -> Vec<ark_grumpkin::Fq> {
+    let mut elements = Vec::new();
+    for input in inputs {
+        match input {
+            ChallengeInput::Field(scalar) => elements.extend(split_scalar(*scalar)),
+            ChallengeInput::Curve(point) => {
+                let (x, y) = point.xy().unwrap_or_default();
+                elements.extend([x, y]);
+            }
+            ChallengeInput::Bytes(bytes) => elements.extend(
+                bytes
+                    .chunks(31)
+                    .map(ark_grumpkin::Fq::from_le_bytes_mod_order),
+            ),
+        }
+    }
+    elements
+}
+
+/// BN254's scalar field is smaller than Grumpkin's, so this does not reduce.
+pub fn to_scalar(element: ark_grumpkin::Fq) -> ark_grumpkin::Fr {
+    ark_grumpkin::Fr::from_le_bytes_mod_order(&element.into_bigint().to_bytes_le())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec};
+    use ark_ec::PrimeGroup;
+    use ark_ff::UniformRand;
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    /// Signs a random message with a delegated token, and checks the signature under `H` and
+    /// under the default [`Blake2s`].
+    fn verify_delegated<H: ChallengeHash<Projective>>() -> (bool, bool) {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective, H>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective, H>::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, _) = AN23ProxySignature::<Projective, H>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let message = Fr::rand(&mut rng);
+        let signature = AN23ProxySignature::<Projective, H>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut tokens,
+            &message,
+        )
+        .unwrap();
+        let verify = |verify: fn(_, _, _, _, &mut Vec<Fr>) -> Result<bool, crate::Error>| {
+            verify(&parameters, &vk, &message, &signature, &mut vec![]).unwrap()
+        };
+        (
+            verify(AN23ProxySignature::<Projective, H>::verify),
+            verify(AN23ProxySignature::<Projective>::verify),
+        )
+    }
+
+    #[test]
+    fn test_challenge_hashes() {
+        assert_eq!(verify_delegated::<Blake2s>(), (true, true));
+        assert_eq!(verify_delegated::<Sha256>(), (true, false));
+        assert_eq!(verify_delegated::<Poseidon2>(), (true, false));
+    }
+
+    #[test]
+    fn test_poseidon2_vector() {
+        // Shared with `test_hash_fcc_poseidon2` in verifier/src/schnorr_token.nr
+        let inputs = [
+            ChallengeInput::Field(Fr::from(7u64)),
+            ChallengeInput::Curve(Projective::generator().into()),
+            ChallengeInput::Curve((Projective::generator() * Fr::from(2u64)).into()),
+        ];
+        let challenge = <Poseidon2 as ChallengeHash<Projective>>::hash(&inputs);
+        assert_eq!(
+            crate::noir_utils::grumpkin_fr_to_nr_code(challenge),
+            "EmbeddedCurveScalar::new(0xdfedb14404a9c5b002385a3a1c6dd097, 0x2ace512de44f4c08f696c8e92e38f408)"
+        );
+    }
+}
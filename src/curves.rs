@@ -0,0 +1,133 @@
+//! Curves besides Grumpkin for [`AN23ProxySignature`](crate::an23_proxy_signature::AN23ProxySignature),
+//! each behind a cargo feature:
+//!
+//! - `secp256k1`: [`secp256k1`], for EVM-side verifiers.
+//! - `bn254`: [`Bn254`], the G1 group of BN254, whose operations are EVM precompiles.
+//! - `pasta`: [`pasta::pallas`] and [`pasta::vesta`].
+//! - `ristretto`: [`ristretto`], ristretto255 over `ark-ed25519`.
+//!
+//! The scheme only needs an arkworks `CurveGroup`.
+
+#[cfg(feature = "pasta")]
+pub mod pasta;
+#[cfg(feature = "ristretto")]
+pub mod ristretto;
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
+
+/// The G1 group of BN254, `ark_bn254::G1Projective` without the pairing-config indirection.
+#[cfg(feature = "bn254")]
+pub type Bn254 = ark_ec::short_weierstrass::Projective<ark_bn254::g1::Config>;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec, Signature};
+    use crate::{Error, NamedCurve};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{FftField, Field, LegendreSymbol, PrimeField, UniformRand};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::test_rng;
+
+    /// The generator is on the curve and has the order of the scalar field, and the generators
+    /// of both fields are non-residues, as needed for square roots when decompressing points.
+    pub fn check_curve<G: CurveGroup>()
+    where
+        G::BaseField: PrimeField,
+    {
+        let generator = G::generator().into_affine();
+        assert!(!generator.is_zero());
+        assert!(generator.mul_bigint(G::ScalarField::MODULUS).is_zero());
+        assert_eq!(
+            G::BaseField::GENERATOR.legendre(),
+            LegendreSymbol::QuadraticNonResidue
+        );
+        assert_eq!(
+            G::ScalarField::GENERATOR.legendre(),
+            LegendreSymbol::QuadraticNonResidue
+        );
+    }
+
+    /// Delegates, signs and verifies over `G`, including revocation and encoding round trips.
+    pub fn check_scheme<G: CurveGroup + NamedCurve>()
+    where
+        G::BaseField: PrimeField,
+    {
+        type Scheme<G> = AN23ProxySignature<G>;
+        let mut rng = test_rng();
+        let parameters = Scheme::<G>::setup(&mut rng).unwrap();
+        let (sk, vk) = Scheme::<G>::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, rev_key) = Scheme::<G>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 2,
+                bind_message_nonce: true,
+            },
+        )
+        .unwrap();
+        let message = G::ScalarField::rand(&mut rng);
+        let signature =
+            Scheme::<G>::delegated_sign(&mut rng, &parameters, &mut tokens, &message).unwrap();
+
+        let mut bytes = Vec::new();
+        signature.serialize_compressed(&mut bytes).unwrap();
+        let decoded = Signature::<G>::deserialize_compressed(&*bytes).unwrap();
+        assert_eq!(compressed_hex(&decoded), hex::encode(&bytes));
+
+        let mut rev_state = Vec::new();
+        assert!(
+            !Scheme::<G>::verify(
+                &parameters,
+                &vk,
+                &(message + G::ScalarField::ONE),
+                &decoded,
+                &mut rev_state
+            )
+            .unwrap()
+        );
+        assert!(Scheme::<G>::verify(&parameters, &vk, &message, &decoded, &mut rev_state).unwrap());
+        assert_eq!(
+            Scheme::<G>::verify(&parameters, &vk, &message, &decoded, &mut rev_state),
+            Err(Error::UseOfRevokedToken)
+        );
+
+        // The other token is revoked by the revocation key
+        let mut rev_state = Vec::new();
+        Scheme::<G>::revoke_in_store(&rev_key, &mut rev_state).unwrap();
+        let signature =
+            Scheme::<G>::delegated_sign(&mut rng, &parameters, &mut tokens, &message).unwrap();
+        assert_eq!(
+            Scheme::<G>::verify(&parameters, &vk, &message, &signature, &mut rev_state),
+            Err(Error::UseOfRevokedToken)
+        );
+
+        let signature = Scheme::<G>::sign(&mut rng, &parameters, &sk, &message, None).unwrap();
+        assert!(
+            Scheme::<G>::verify(&parameters, &vk, &message, &signature, &mut Vec::new()).unwrap()
+        );
+        assert!(!G::NAME.is_empty());
+    }
+
+    pub fn compressed_hex<T: CanonicalSerialize>(value: &T) -> String {
+        let mut bytes = Vec::new();
+        value.serialize_compressed(&mut bytes).unwrap();
+        hex::encode(bytes)
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_bn254() {
+        use super::Bn254;
+        use ark_ec::PrimeGroup;
+
+        check_curve::<Bn254>();
+        check_scheme::<Bn254>();
+        // The generator (1, 2), with the flags in the top bits
+        assert_eq!(
+            compressed_hex(&Bn254::generator()),
+            "0100000000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+}
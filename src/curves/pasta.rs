@@ -0,0 +1,45 @@
+//! The Pasta curves of Halo 2: Pallas and Vesta, `y^2 = x^3 + 5` over each other's scalar
+//! fields, from `ark-pallas` and `ark-vesta`.
+
+/// The base field of Pallas and the scalar field of Vesta, and the other way around.
+pub use ark_pallas::{Fq, Fr};
+
+pub mod pallas {
+    pub use ark_pallas::{Affine, PallasConfig as Config, Projective};
+}
+
+pub mod vesta {
+    pub use ark_vesta::{Affine, Projective, VestaConfig as Config};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::tests::{check_curve, check_scheme, compressed_hex};
+    use ark_ec::PrimeGroup;
+
+    #[test]
+    fn test_pallas() {
+        check_curve::<pallas::Projective>();
+        check_scheme::<pallas::Projective>();
+    }
+
+    #[test]
+    fn test_vesta() {
+        check_curve::<vesta::Projective>();
+        check_scheme::<vesta::Projective>();
+    }
+
+    #[test]
+    fn test_serialization_vectors() {
+        // x = -1, little-endian, with the flags in an extra byte
+        assert_eq!(
+            compressed_hex(&pallas::Projective::generator()),
+            "00000000ed302d991bf94c09fc9846220000000000000000000000000000004000"
+        );
+        assert_eq!(
+            compressed_hex(&vesta::Projective::generator()),
+            "0000000021eb468cdda89409fc9846220000000000000000000000000000004000"
+        );
+    }
+}
@@ -0,0 +1,588 @@
+//! ristretto255 ([RFC 9496](https://www.rfc-editor.org/rfc/rfc9496)), the prime-order group
+//! built on Curve25519, over the twisted Edwards form of `ark-ed25519`.
+//!
+//! An element is a class of Edwards points that differ by a point of order 4. [`Projective`]
+//! keeps any point of the class and compares classes, while [`Affine`] keeps the point decoded
+//! from the canonical encoding, so that its coordinates, which the challenges hash, identify the
+//! element. Both serialize to the 32-byte encoding of the RFC, and decoding rejects non-canonical
+//! encodings. Like the other curves, the arithmetic is not constant time.
+
+use ark_ec::models::CurveConfig;
+use ark_ec::scalar_mul::ScalarMul;
+use ark_ec::scalar_mul::variable_base::VariableBaseMSM;
+use ark_ec::twisted_edwards::TECurveConfig;
+use ark_ec::{AdditiveGroup, AffineRepr, CurveGroup, PrimeGroup};
+use ark_ed25519::{EdwardsAffine, EdwardsConfig, EdwardsProjective};
+use ark_ff::{BigInt, BigInteger, Field, MontFp, One, PrimeField, Zero};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::UniformRand;
+use ark_std::rand::Rng;
+use ark_std::rand::distributions::{Distribution, Standard};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use zeroize::Zeroize;
+
+pub use ark_ed25519::{Fq, Fr};
+
+/// `sqrt(-1)`, the non-negative root.
+const SQRT_M1: Fq =
+    MontFp!("19681161376707505956807079304988542015446066515923890162744021073123829784752");
+
+/// `1 / sqrt(a - d)`, the non-negative root.
+const INVSQRT_A_MINUS_D: Fq =
+    MontFp!("54469307008909316920995813868745141605393597292927456921205312896311721017578");
+
+/// `(p - 5) / 8`, the exponent of the square roots modulo `p = 2^255 - 19`.
+const SQRT_EXPONENT: [u64; 4] = [
+    0xfffffffffffffffd,
+    0xffffffffffffffff,
+    0xffffffffffffffff,
+    0x0fffffffffffffff,
+];
+
+/// The length of an encoding.
+pub const ENCODED_LEN: usize = 32;
+
+/// ristretto255 has prime order, so its cofactor is 1.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct Config;
+
+impl CurveConfig for Config {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = 1
+    const COFACTOR_INV: Fr = Fr::ONE;
+}
+
+/// An element, as any Edwards point of its class.
+#[derive(Copy, Clone, Debug)]
+pub struct Projective(EdwardsProjective);
+
+/// An element, as the Edwards point decoded from its encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Affine(EdwardsAffine);
+
+fn is_negative(x: &Fq) -> bool {
+    x.into_bigint().is_odd()
+}
+
+fn abs(x: Fq) -> Fq {
+    if is_negative(&x) { -x } else { x }
+}
+
+/// `SQRT_RATIO_M1` of the RFC: whether `u / v` is a square, and the non-negative square root of
+/// `u / v` or of `sqrt(-1) * u / v`.
+fn sqrt_ratio_m1(u: Fq, v: Fq) -> (bool, Fq) {
+    let v3 = v.square() * v;
+    let v7 = v3.square() * v;
+    let mut r = u * v3 * (u * v7).pow(SQRT_EXPONENT);
+    let check = v * r.square();
+    let correct_sign = check == u;
+    let flipped_sign = check == -u;
+    let flipped_sign_i = check == -u * SQRT_M1;
+    if flipped_sign || flipped_sign_i {
+        r *= SQRT_M1;
+    }
+    (correct_sign || flipped_sign, abs(r))
+}
+
+fn encode(point: &EdwardsProjective) -> [u8; ENCODED_LEN] {
+    let (x0, y0, z0, t0) = (point.x, point.y, point.z, point.t);
+    let u1 = (z0 + y0) * (z0 - y0);
+    let u2 = x0 * y0;
+    let (_, invsqrt) = sqrt_ratio_m1(Fq::one(), u1 * u2.square());
+    let den1 = invsqrt * u1;
+    let den2 = invsqrt * u2;
+    let z_inv = den1 * den2 * t0;
+    let (x, mut y, den_inv) = if is_negative(&(t0 * z_inv)) {
+        (y0 * SQRT_M1, x0 * SQRT_M1, den1 * INVSQRT_A_MINUS_D)
+    } else {
+        (x0, y0, den2)
+    };
+    if is_negative(&(x * z_inv)) {
+        y = -y;
+    }
+    let s = abs(den_inv * (z0 - y));
+    let mut bytes = [0; ENCODED_LEN];
+    bytes.copy_from_slice(&s.into_bigint().to_bytes_le());
+    bytes
+}
+
+fn decode(bytes: &[u8; ENCODED_LEN]) -> Option<EdwardsAffine> {
+    let limbs =
+        std::array::from_fn(|i| u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap()));
+    let s = Fq::from_bigint(BigInt(limbs))?;
+    if is_negative(&s) {
+        return None;
+    }
+    let ss = s.square();
+    let u1 = Fq::one() - ss;
+    let u2 = Fq::one() + ss;
+    let u2_sqr = u2.square();
+    let v = -(EdwardsConfig::COEFF_D * u1.square()) - u2_sqr;
+    let (was_square, invsqrt) = sqrt_ratio_m1(Fq::one(), v * u2_sqr);
+    let den_x = invsqrt * u2;
+    let den_y = invsqrt * den_x * v;
+    let x = abs(s.double() * den_x);
+    let y = u1 * den_y;
+    if !was_square || is_negative(&(x * y)) || y.is_zero() {
+        return None;
+    }
+    Some(EdwardsAffine::new_unchecked(x, y))
+}
+
+impl Projective {
+    /// The encoding of the element.
+    pub fn encode(&self) -> [u8; ENCODED_LEN] {
+        encode(&self.0)
+    }
+}
+
+impl Affine {
+    /// Decodes a canonical encoding.
+    pub fn decode(bytes: &[u8; ENCODED_LEN]) -> Option<Self> {
+        decode(bytes).map(Self)
+    }
+}
+
+impl From<Affine> for Projective {
+    fn from(point: Affine) -> Self {
+        Self(point.0.into())
+    }
+}
+
+impl From<Projective> for Affine {
+    fn from(point: Projective) -> Self {
+        Self(decode(&point.encode()).expect("encodings decode"))
+    }
+}
+
+impl PartialEq for Projective {
+    /// Points of the same class, which differ by a point of order 4.
+    fn eq(&self, other: &Self) -> bool {
+        let (p, q) = (&self.0, &other.0);
+        p.x * q.y == p.y * q.x || p.y * q.y == p.x * q.x
+    }
+}
+
+impl Eq for Projective {}
+
+impl Hash for Projective {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.encode().hash(state)
+    }
+}
+
+impl Default for Projective {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl fmt::Display for Projective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.encode()))
+    }
+}
+
+impl fmt::Display for Affine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Projective::from(*self).fmt(f)
+    }
+}
+
+impl Zeroize for Projective {
+    fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+impl Zeroize for Affine {
+    fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+impl Distribution<Projective> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Projective {
+        Projective(EdwardsProjective::rand(rng))
+    }
+}
+
+impl Distribution<Affine> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Affine {
+        Projective::rand(rng).into()
+    }
+}
+
+impl Zero for Projective {
+    fn zero() -> Self {
+        Self::ZERO
+    }
+
+    /// The class of the identity holds the points with `x = 0` or `y = 0`.
+    fn is_zero(&self) -> bool {
+        self.0.x.is_zero() || self.0.y.is_zero()
+    }
+}
+
+impl Neg for Projective {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl Neg for Affine {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Projective::from(self).neg().into()
+    }
+}
+
+/// Implements `$op<$rhs>` for `Projective` and its assigning counterpart, through `Projective`.
+macro_rules! impl_projective_op {
+    ($op:ident, $f:ident, $assign:ident, $assign_f:ident, $rhs:ty) => {
+        impl $op<$rhs> for Projective {
+            type Output = Self;
+
+            fn $f(self, other: $rhs) -> Self {
+                Self(self.0.$f(Projective::from(other).0))
+            }
+        }
+
+        impl<'a> $op<&'a $rhs> for Projective {
+            type Output = Self;
+
+            fn $f(self, other: &'a $rhs) -> Self {
+                self.$f(*other)
+            }
+        }
+
+        impl<'a> $op<&'a mut $rhs> for Projective {
+            type Output = Self;
+
+            fn $f(self, other: &'a mut $rhs) -> Self {
+                self.$f(*other)
+            }
+        }
+
+        impl $assign<$rhs> for Projective {
+            fn $assign_f(&mut self, other: $rhs) {
+                *self = self.$f(other);
+            }
+        }
+
+        impl<'a> $assign<&'a $rhs> for Projective {
+            fn $assign_f(&mut self, other: &'a $rhs) {
+                *self = self.$f(*other);
+            }
+        }
+
+        impl<'a> $assign<&'a mut $rhs> for Projective {
+            fn $assign_f(&mut self, other: &'a mut $rhs) {
+                *self = self.$f(*other);
+            }
+        }
+    };
+}
+
+impl_projective_op!(Add, add, AddAssign, add_assign, Projective);
+impl_projective_op!(Sub, sub, SubAssign, sub_assign, Projective);
+impl_projective_op!(Add, add, AddAssign, add_assign, Affine);
+impl_projective_op!(Sub, sub, SubAssign, sub_assign, Affine);
+
+/// Implements `$op<$rhs>` for `Affine`, with a `Projective` result.
+macro_rules! impl_affine_op {
+    ($op:ident, $f:ident, $rhs:ty) => {
+        impl $op<$rhs> for Affine {
+            type Output = Projective;
+
+            fn $f(self, other: $rhs) -> Projective {
+                Projective::from(self).$f(other)
+            }
+        }
+
+        impl<'a> $op<&'a $rhs> for Affine {
+            type Output = Projective;
+
+            fn $f(self, other: &'a $rhs) -> Projective {
+                Projective::from(self).$f(*other)
+            }
+        }
+    };
+}
+
+impl_affine_op!(Add, add, Affine);
+impl_affine_op!(Sub, sub, Affine);
+impl_affine_op!(Add, add, Projective);
+impl_affine_op!(Sub, sub, Projective);
+impl_affine_op!(Mul, mul, Fr);
+
+impl Mul<Fr> for Projective {
+    type Output = Self;
+
+    fn mul(self, scalar: Fr) -> Self {
+        Self(self.0 * scalar)
+    }
+}
+
+impl<'a> Mul<&'a Fr> for Projective {
+    type Output = Self;
+
+    fn mul(self, scalar: &'a Fr) -> Self {
+        self * *scalar
+    }
+}
+
+impl<'a> Mul<&'a mut Fr> for Projective {
+    type Output = Self;
+
+    fn mul(self, scalar: &'a mut Fr) -> Self {
+        self * *scalar
+    }
+}
+
+impl MulAssign<Fr> for Projective {
+    fn mul_assign(&mut self, scalar: Fr) {
+        *self = *self * scalar;
+    }
+}
+
+impl<'a> MulAssign<&'a Fr> for Projective {
+    fn mul_assign(&mut self, scalar: &'a Fr) {
+        *self = *self * *scalar;
+    }
+}
+
+impl<'a> MulAssign<&'a mut Fr> for Projective {
+    fn mul_assign(&mut self, scalar: &'a mut Fr) {
+        *self = *self * *scalar;
+    }
+}
+
+impl Sum<Projective> for Projective {
+    fn sum<I: Iterator<Item = Projective>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Projective> for Projective {
+    fn sum<I: Iterator<Item = &'a Projective>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl Sum<Affine> for Projective {
+    fn sum<I: Iterator<Item = Affine>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Affine> for Projective {
+    fn sum<I: Iterator<Item = &'a Affine>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+/// Implements the canonical serialization of a type convertible to and from `Projective`: the
+/// encoding, compressed or not.
+macro_rules! impl_serialization {
+    ($t:ty) => {
+        impl CanonicalSerialize for $t {
+            fn serialize_with_mode<W: Write>(
+                &self,
+                mut writer: W,
+                _: Compress,
+            ) -> Result<(), SerializationError> {
+                Ok(writer.write_all(&Projective::from(*self).encode())?)
+            }
+
+            fn serialized_size(&self, _: Compress) -> usize {
+                ENCODED_LEN
+            }
+        }
+
+        impl Valid for $t {
+            /// Decoding checks the encoding.
+            fn check(&self) -> Result<(), SerializationError> {
+                Ok(())
+            }
+        }
+
+        impl CanonicalDeserialize for $t {
+            fn deserialize_with_mode<R: Read>(
+                mut reader: R,
+                _: Compress,
+                _: Validate,
+            ) -> Result<Self, SerializationError> {
+                let mut bytes = [0; ENCODED_LEN];
+                reader.read_exact(&mut bytes)?;
+                let point = Affine::decode(&bytes).ok_or(SerializationError::InvalidData)?;
+                Ok(point.into())
+            }
+        }
+    };
+}
+
+impl_serialization!(Projective);
+impl_serialization!(Affine);
+
+impl AdditiveGroup for Projective {
+    type Scalar = Fr;
+
+    const ZERO: Self = Self(EdwardsProjective::ZERO);
+
+    fn double_in_place(&mut self) -> &mut Self {
+        self.0.double_in_place();
+        self
+    }
+}
+
+impl PrimeGroup for Projective {
+    type ScalarField = Fr;
+
+    fn generator() -> Self {
+        Self(EdwardsProjective::generator())
+    }
+
+    fn mul_bigint(&self, other: impl AsRef<[u64]>) -> Self {
+        Self(self.0.mul_bigint(other))
+    }
+}
+
+impl ScalarMul for Projective {
+    type MulBase = Affine;
+
+    const NEGATION_IS_CHEAP: bool = true;
+
+    fn batch_convert_to_mul_base(bases: &[Self]) -> Vec<Affine> {
+        Self::normalize_batch(bases)
+    }
+}
+
+impl VariableBaseMSM for Projective {}
+
+impl CurveGroup for Projective {
+    type Config = Config;
+    type BaseField = Fq;
+    type Affine = Affine;
+    type FullGroup = Affine;
+
+    fn normalize_batch(v: &[Self]) -> Vec<Affine> {
+        v.iter().map(|&point| point.into()).collect()
+    }
+}
+
+impl AffineRepr for Affine {
+    type Config = Config;
+    type ScalarField = Fr;
+    type BaseField = Fq;
+    type Group = Projective;
+
+    /// The coordinates of the decoded point, `None` for the identity.
+    fn xy(&self) -> Option<(Fq, Fq)> {
+        (!self.0.is_zero()).then_some((self.0.x, self.0.y))
+    }
+
+    fn zero() -> Self {
+        Self(EdwardsAffine::zero())
+    }
+
+    fn generator() -> Self {
+        Projective::generator().into()
+    }
+
+    /// Decodes the first 32 bytes.
+    fn from_random_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::decode(bytes.get(..ENCODED_LEN)?.try_into().ok()?)
+    }
+
+    fn mul_bigint(&self, by: impl AsRef<[u64]>) -> Projective {
+        Projective::from(*self).mul_bigint(by)
+    }
+
+    fn clear_cofactor(&self) -> Self {
+        *self
+    }
+
+    fn mul_by_cofactor_to_group(&self) -> Projective {
+        (*self).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::tests::{check_curve, check_scheme, compressed_hex};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_ristretto() {
+        check_curve::<Projective>();
+        check_scheme::<Projective>();
+    }
+
+    #[test]
+    fn test_encoding_vectors() {
+        // The multiples of the generator in RFC 9496, appendix A.1
+        let multiples = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76",
+            "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+            "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259",
+        ];
+        let mut point = Projective::zero();
+        for expected in multiples {
+            assert_eq!(compressed_hex(&point), expected);
+            let bytes = hex::decode(expected).unwrap();
+            assert_eq!(Projective::deserialize_compressed(&*bytes).unwrap(), point);
+            point += Projective::generator();
+        }
+
+        // Non-canonical and negative field elements, and a non-square
+        for invalid in [
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "0200000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            let bytes = hex::decode(invalid).unwrap();
+            assert!(Projective::deserialize_compressed(&*bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_torsion_is_ignored() {
+        // Adding a point of order 2 or 4 to an Edwards point leaves its element unchanged
+        let point = Projective::rand(&mut test_rng());
+        for torsion in [
+            EdwardsAffine::new_unchecked(Fq::zero(), -Fq::one()),
+            EdwardsAffine::new_unchecked(SQRT_M1, Fq::zero()),
+        ] {
+            let shifted = Projective(point.0 + torsion);
+            assert_eq!(shifted, point);
+            assert_eq!(shifted.encode(), point.encode());
+            assert_eq!(shifted.into_affine(), point.into_affine());
+            assert!(Projective(torsion.into()).is_zero());
+        }
+        assert_ne!(point, -point);
+        assert!((point - point).is_zero());
+    }
+}
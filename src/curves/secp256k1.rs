@@ -0,0 +1,30 @@
+//! secp256k1, the curve of Bitcoin and Ethereum signatures, from `ark-secp256k1`.
+
+pub use ark_secp256k1::{Affine, Config, Fq, Fr, Projective};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::tests::{check_curve, check_scheme, compressed_hex};
+    use ark_ec::{AdditiveGroup, PrimeGroup};
+
+    #[test]
+    fn test_secp256k1() {
+        check_curve::<Projective>();
+        check_scheme::<Projective>();
+    }
+
+    #[test]
+    fn test_serialization_vectors() {
+        // The well-known x-coordinate of 2G, little-endian, with the flags in an extra byte
+        let double = Projective::generator().double();
+        assert_eq!(
+            compressed_hex(&double),
+            "e59e705cb909acaba73cef8c4b8e775cd87cc0956e4045306d7ded41947f04c600"
+        );
+        assert_eq!(
+            compressed_hex(&Fr::from(1u64)),
+            "0100000000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Streaming (de)serialization of delegations.
+//!
+//! The streams use the same layout as `CanonicalSerialize` for a `Vec` (a `u64` length followed by
+//! the items), so a streamed delegation can still be read with
+//! `DelegationInfo::deserialize_compressed`, and vice versa.
+
+use crate::Error;
+use crate::an23_proxy_signature::{
+    AN23ProxySignature, DelegationSpec, Parameters, SigningKey, SigningToken,
+};
+use crate::challenge_hash::ChallengeHash;
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use rand::Rng;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+/// Writes a known number of items one at a time.
+pub struct StreamWriter<W: Write> {
+    writer: W,
+    remaining: u64,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Writes the length header for `len` items.
+    pub fn new(mut writer: W, len: u64) -> Result<Self, SerializationError> {
+        len.serialize_compressed(&mut writer)?;
+        Ok(Self {
+            writer,
+            remaining: len,
+        })
+    }
+
+    pub fn write<T: CanonicalSerialize>(&mut self, item: &T) -> Result<(), SerializationError> {
+        if self.remaining == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        item.serialize_compressed(&mut self.writer)?;
+        self.remaining -= 1;
+        Ok(())
+    }
+
+    /// Checks that all announced items were written and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, SerializationError> {
+        if self.remaining != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads items one at a time from a stream written by [`StreamWriter`] or `CanonicalSerialize`.
+pub struct StreamReader<R: Read, T> {
+    reader: R,
+    remaining: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<R: Read, T: CanonicalDeserialize> StreamReader<R, T> {
+    /// Reads the length header.
+    pub fn new(mut reader: R) -> Result<Self, SerializationError> {
+        let remaining = u64::deserialize_compressed(&mut reader)?;
+        Ok(Self {
+            reader,
+            remaining,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Number of items left in the stream.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<R: Read, T: CanonicalDeserialize> Iterator for StreamReader<R, T> {
+    type Item = Result<T, SerializationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(T::deserialize_compressed(&mut self.reader))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+/// Generates a delegation for `AN23ProxySignature<G, H>` and streams the signing tokens to
+/// `delegation_writer` and the revocation key to `rev_key_writer`, holding a single token in
+/// memory at a time.
+pub fn stream_delegation<G, H, R, W1, W2>(
+    rng: &mut R,
+    parameters: &Parameters<G>,
+    sk: &SigningKey<G>,
+    deg_spec: &DelegationSpec,
+    delegation_writer: W1,
+    rev_key_writer: W2,
+) -> Result<(W1, W2), Error>
+where
+    G: CurveGroup,
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+    H: ChallengeHash<G>,
+    R: Rng,
+    W1: Write,
+    W2: Write,
+{
+    let mut delegation_writer = StreamWriter::new(delegation_writer, deg_spec.number_of_tokens)
+        .map_err(|_| Error::SerializationFailed)?;
+    let mut rev_key_writer = StreamWriter::new(rev_key_writer, deg_spec.number_of_tokens)
+        .map_err(|_| Error::SerializationFailed)?;
+
+    for item in AN23ProxySignature::<G, H>::delegate_iter(rng, parameters, sk, deg_spec) {
+        let (signing_token, rev_key): (SigningToken<G>, _) = item?;
+        delegation_writer
+            .write(&signing_token)
+            .map_err(|_| Error::SerializationFailed)?;
+        rev_key_writer
+            .write(&rev_key)
+            .map_err(|_| Error::SerializationFailed)?;
+    }
+
+    Ok((
+        delegation_writer
+            .finish()
+            .map_err(|_| Error::SerializationFailed)?,
+        rev_key_writer
+            .finish()
+            .map_err(|_| Error::SerializationFailed)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::challenge_hash::{Blake2s, Sha256};
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_streamed_delegation_matches_delegate() {
+        check_streamed_delegation::<Blake2s>();
+        check_streamed_delegation::<Sha256>();
+    }
+
+    fn check_streamed_delegation<H: ChallengeHash<Projective>>() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective, H>::setup(&mut rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective, H>::keygen(&mut rng, &parameters).unwrap();
+        let deg_spec = DelegationSpec {
+            number_of_tokens: 10,
+            bind_message_nonce: false,
+        };
+
+        let (delegation_bytes, rev_key_bytes) = stream_delegation::<_, H, _, _, _>(
+            &mut test_rng(),
+            &parameters,
+            &sk,
+            &deg_spec,
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        let (delegation_info, rev_key) = AN23ProxySignature::<Projective, H>::delegate(
+            &mut test_rng(),
+            &parameters,
+            &sk,
+            &deg_spec,
+        )
+        .unwrap();
+
+        let mut expected_bytes = Vec::new();
+        delegation_info
+            .serialize_compressed(&mut expected_bytes)
+            .unwrap();
+        assert_eq!(delegation_bytes, expected_bytes);
+        assert_eq!(
+            Vec::<Fr>::deserialize_compressed(&*rev_key_bytes).unwrap(),
+            rev_key
+        );
+
+        let reader = StreamReader::<_, SigningToken<Projective>>::new(&*delegation_bytes).unwrap();
+        assert_eq!(reader.remaining(), 10);
+        let m0s = reader.map(|token| token.unwrap().m0).collect::<Vec<_>>();
+        assert_eq!(m0s, rev_key);
+    }
+
+    #[test]
+    fn test_stream_writer_checks_length() {
+        let mut writer = StreamWriter::new(Vec::new(), 1).unwrap();
+        assert!(writer.write(&Fr::from(1u64)).is_ok());
+        assert!(writer.write(&Fr::from(2u64)).is_err());
+
+        let writer = StreamWriter::new(Vec::new(), 1).unwrap();
+        assert!(writer.finish().is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField};
+
+/// Default window size in bits. A table for a 256-bit scalar field holds 32 * 256 points.
+pub const DEFAULT_WINDOW: usize = 8;
+
+/// Windowed precomputation of the multiples of a fixed base point.
+///
+/// Entry `table[i][j]` holds `[j * 2^(window * i)]B`, so that `[s]B` is the sum of one entry per
+/// window of the scalar `s`, without any doubling.
+#[derive(Clone, Debug)]
+pub struct FixedBaseTable<G: CurveGroup> {
+    window: usize,
+    table: Vec<Vec<G::Affine>>,
+}
+
+impl<G: CurveGroup> FixedBaseTable<G>
+where
+    G::ScalarField: PrimeField,
+{
+    pub fn new(base: G, window: usize) -> Self {
+        assert!(
+            (1..=16).contains(&window),
+            "window must be between 1 and 16 bits"
+        );
+
+        let num_bits = G::ScalarField::MODULUS_BIT_SIZE as usize;
+        let num_windows = num_bits.div_ceil(window);
+
+        let mut points = Vec::with_capacity(num_windows << window);
+        let mut window_base = base;
+        for _ in 0..num_windows {
+            let mut multiple = G::zero();
+            for _ in 0..(1 << window) {
+                points.push(multiple);
+                multiple += window_base;
+            }
+            window_base = multiple; // [2^window] * window_base
+        }
+
+        let table = G::normalize_batch(&points)
+            .chunks_exact(1 << window)
+            .map(<[G::Affine]>::to_vec)
+            .collect();
+
+        Self { window, table }
+    }
+
+    pub fn mul(&self, scalar: &G::ScalarField) -> G {
+        let bits = scalar.into_bigint().to_bits_le();
+
+        let mut result = G::zero();
+        for (window_table, window_bits) in self.table.iter().zip(bits.chunks(self.window)) {
+            let digit = window_bits
+                .iter()
+                .rev()
+                .fold(0usize, |acc, &bit| (acc << 1) | bit as usize);
+            if digit != 0 {
+                result += &window_table[digit];
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::PrimeGroup;
+    use ark_ff::{Field, UniformRand};
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_fixed_base_mul() {
+        let mut rng = test_rng();
+        let base = Projective::generator();
+
+        for window in [1, 4, 5, DEFAULT_WINDOW] {
+            let table = FixedBaseTable::new(base, window);
+
+            for scalar in [Fr::from(0u64), Fr::ONE, -Fr::ONE, Fr::rand(&mut rng)] {
+                assert_eq!(table.mul(&scalar), base * scalar);
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Mnemonic backups and hierarchical derivation of signing keys.
+//!
+//! A signing key is backed up as the [BIP39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki)
+//! mnemonic of its 32-byte little-endian encoding. Child keys are derived BIP32-style: the chain
+//! code of a root key is a hash of the key, so the mnemonic of the root key is enough to restore
+//! the whole tree.
+//!
+//! Schnorr keys are additively homomorphic, so non-hardened children `sk + t` have verification
+//! keys `vk + t·G` that can be derived from an [`ExtendedVerificationKey`] alone. As in BIP32,
+//! a non-hardened child signing key together with the parent extended verification key reveals
+//! the parent signing key; use hardened indices where that matters.
+
+use crate::Error;
+use crate::an23_proxy_signature::{Parameters, SigningKey, VerificationKey};
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bip39::Mnemonic;
+use blake2::{Blake2b512, Blake2s256, Digest};
+
+/// Indices from `HARDENED` up derive hardened children, which need the parent signing key.
+pub const HARDENED: u32 = 1 << 31;
+
+const CHAIN_CODE_TAG: &[u8] = b"schnorr-tokens/chain-code";
+const TWEAK_TAG: &[u8] = b"schnorr-tokens/tweak";
+
+impl<G: CurveGroup> SigningKey<G>
+where
+    G::ScalarField: PrimeField,
+{
+    /// The 24-word English mnemonic of the key.
+    pub fn to_mnemonic(&self) -> Result<String, Error> {
+        let mnemonic = Mnemonic::from_entropy(&scalar_to_bytes(&self.0))
+            .map_err(|_| Error::InvalidMnemonic)?;
+        Ok(mnemonic.to_string())
+    }
+
+    /// Restores a key from its mnemonic. Mnemonics that do not encode a canonical, non-zero
+    /// scalar are rejected.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, Error> {
+        let entropy = Mnemonic::parse(phrase)
+            .map_err(|_| Error::InvalidMnemonic)?
+            .to_entropy();
+        let scalar = G::ScalarField::from_le_bytes_mod_order(&entropy);
+        if scalar.is_zero() || scalar_to_bytes(&scalar) != entropy {
+            return Err(Error::InvalidMnemonic);
+        }
+        Ok(Self(scalar))
+    }
+}
+
+/// A signing key together with the chain code needed to derive its children.
+#[derive(Clone, Debug)]
+pub struct ExtendedSigningKey<G: CurveGroup> {
+    pub signing_key: SigningKey<G>,
+    chain_code: [u8; 32],
+}
+
+/// A verification key together with the chain code needed to derive its non-hardened children.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct ExtendedVerificationKey<G: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    pub verification_key: VerificationKey<G>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    chain_code: [u8; 32],
+}
+
+impl<G: CurveGroup> ExtendedSigningKey<G>
+where
+    G::ScalarField: PrimeField,
+{
+    /// The root of the key tree of `signing_key`.
+    pub fn new(signing_key: SigningKey<G>) -> Self {
+        let mut hasher = Blake2s256::new();
+        hasher.update(CHAIN_CODE_TAG);
+        hasher.update(scalar_to_bytes(&signing_key.0));
+        Self {
+            signing_key,
+            chain_code: hasher.finalize().into(),
+        }
+    }
+
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, Error> {
+        SigningKey::from_mnemonic(phrase).map(Self::new)
+    }
+
+    pub fn extended_verification_key(
+        &self,
+        parameters: &Parameters<G>,
+    ) -> ExtendedVerificationKey<G> {
+        ExtendedVerificationKey {
+            verification_key: parameters.mul_generator(self.signing_key.0).into(),
+            chain_code: self.chain_code,
+        }
+    }
+
+    pub fn derive_child(&self, parameters: &Parameters<G>, index: u32) -> Result<Self, Error> {
+        let data = if index >= HARDENED {
+            let mut data = vec![0];
+            data.extend(scalar_to_bytes(&self.signing_key.0));
+            data
+        } else {
+            point_to_bytes::<G>(&parameters.mul_generator(self.signing_key.0).into())?
+        };
+
+        let (tweak, chain_code) = derive::<G::ScalarField>(&self.chain_code, &data, index);
+        let child = self.signing_key.0 + tweak;
+        if child.is_zero() {
+            return Err(Error::DerivationFailed);
+        }
+        Ok(Self {
+            signing_key: SigningKey(child),
+            chain_code,
+        })
+    }
+
+    /// Derives along `path`, e.g. one parsed with [`parse_path`].
+    pub fn derive_path(&self, parameters: &Parameters<G>, path: &[u32]) -> Result<Self, Error> {
+        path.iter().try_fold(self.clone(), |key, &index| {
+            key.derive_child(parameters, index)
+        })
+    }
+}
+
+impl<G: CurveGroup> ExtendedVerificationKey<G>
+where
+    G::ScalarField: PrimeField,
+{
+    /// Derives the verification key of the non-hardened child `index`. Fails with
+    /// [`Error::DerivationFailed`] for hardened indices.
+    pub fn derive_child(&self, parameters: &Parameters<G>, index: u32) -> Result<Self, Error> {
+        if index >= HARDENED {
+            return Err(Error::DerivationFailed);
+        }
+
+        let data = point_to_bytes::<G>(&self.verification_key)?;
+        let (tweak, chain_code) = derive(&self.chain_code, &data, index);
+        let child = parameters.mul_generator(tweak) + self.verification_key;
+        if child.is_zero() {
+            return Err(Error::DerivationFailed);
+        }
+        Ok(Self {
+            verification_key: child.into_affine(),
+            chain_code,
+        })
+    }
+
+    pub fn derive_path(&self, parameters: &Parameters<G>, path: &[u32]) -> Result<Self, Error> {
+        path.iter().try_fold(self.clone(), |key, &index| {
+            key.derive_child(parameters, index)
+        })
+    }
+}
+
+/// Parses a path like `m/0'/3/1h`. Indices marked with `'` or `h` are hardened.
+pub fn parse_path(path: &str) -> Result<Vec<u32>, Error> {
+    let mut components = path.split('/');
+    if components.next() != Some("m") {
+        return Err(Error::DerivationFailed);
+    }
+    components
+        .map(|component| {
+            let (index, offset) = match component
+                .strip_suffix('\'')
+                .or_else(|| component.strip_suffix('h'))
+            {
+                Some(index) => (index, HARDENED),
+                None => (component, 0),
+            };
+            match index.parse::<u32>() {
+                Ok(index) if index < HARDENED => Ok(index + offset),
+                _ => Err(Error::DerivationFailed),
+            }
+        })
+        .collect()
+}
+
+/// Returns the tweak added to the parent key and the child chain code.
+fn derive<F: PrimeField>(chain_code: &[u8; 32], data: &[u8], index: u32) -> (F, [u8; 32]) {
+    // A 512-bit hash keeps the reduction bias negligible
+    let mut hasher = Blake2b512::new();
+    hasher.update(TWEAK_TAG);
+    hasher.update(chain_code);
+    hasher.update(data);
+    hasher.update(index.to_be_bytes());
+    let tweak = F::from_le_bytes_mod_order(&hasher.finalize());
+
+    let mut hasher = Blake2s256::new();
+    hasher.update(CHAIN_CODE_TAG);
+    hasher.update(chain_code);
+    hasher.update(data);
+    hasher.update(index.to_be_bytes());
+
+    (tweak, hasher.finalize().into())
+}
+
+fn scalar_to_bytes<F: PrimeField>(scalar: &F) -> Vec<u8> {
+    let mut bytes = scalar.into_bigint().to_bytes_le();
+    bytes.truncate(F::MODULUS_BIT_SIZE.div_ceil(8) as usize);
+    bytes
+}
+
+fn point_to_bytes<G: CurveGroup>(point: &G::Affine) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    point
+        .serialize_compressed(&mut bytes)
+        .map_err(|_| Error::SerializationFailed)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::AN23ProxySignature;
+    use ark_ff::UniformRand;
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_mnemonic_round_trip() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let phrase = sk.to_mnemonic().unwrap();
+        assert_eq!(phrase.split(' ').count(), 24);
+        let restored = SigningKey::<Projective>::from_mnemonic(&phrase).unwrap();
+        assert_eq!(restored.0, sk.0);
+
+        // All-ones entropy is larger than the scalar field modulus
+        let non_canonical = Mnemonic::from_entropy(&[0xff; 32]).unwrap().to_string();
+        assert_eq!(
+            SigningKey::<Projective>::from_mnemonic(&non_canonical).unwrap_err(),
+            Error::InvalidMnemonic
+        );
+        assert_eq!(
+            SigningKey::<Projective>::from_mnemonic("not a mnemonic").unwrap_err(),
+            Error::InvalidMnemonic
+        );
+    }
+
+    #[test]
+    fn test_public_derivation_matches_private_derivation() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let root = ExtendedSigningKey::<Projective>::new(SigningKey(Fr::rand(&mut rng)));
+
+        let path = parse_path("m/7'/2/5").unwrap();
+        assert_eq!(path, vec![HARDENED + 7, 2, 5]);
+        let child = root.derive_path(&parameters, &path).unwrap();
+
+        // The department key only needs the extended verification key of m/7'
+        let department = root
+            .derive_child(&parameters, HARDENED + 7)
+            .unwrap()
+            .extended_verification_key(&parameters);
+        assert_eq!(
+            department.derive_path(&parameters, &[2, 5]).unwrap(),
+            child.extended_verification_key(&parameters)
+        );
+        assert_eq!(
+            department.derive_child(&parameters, HARDENED).unwrap_err(),
+            Error::DerivationFailed
+        );
+
+        // The tree is restored from the root mnemonic
+        let restored = ExtendedSigningKey::<Projective>::from_mnemonic(
+            &root.signing_key.to_mnemonic().unwrap(),
+        )
+        .unwrap()
+        .derive_path(&parameters, &path)
+        .unwrap();
+        assert_eq!(restored.signing_key.0, child.signing_key.0);
+
+        // Child keys sign like any other key
+        let message = Fr::rand(&mut rng);
+        let vk = child
+            .extended_verification_key(&parameters)
+            .verification_key;
+        let signature = AN23ProxySignature::<Projective>::sign(
+            &mut rng,
+            &parameters,
+            &child.signing_key,
+            &message,
+            None,
+        )
+        .unwrap();
+        assert!(
+            AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut Vec::new(),
+            )
+            .unwrap()
+        );
+
+        assert!(parse_path("0/1").is_err());
+        assert!(parse_path("m/2147483648").is_err());
+    }
+}
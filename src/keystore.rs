@@ -0,0 +1,410 @@
+//! Password-encrypted storage for signing keys.
+//!
+//! The signing key is encrypted with XChaCha20-Poly1305 under a key derived from the password
+//! with Argon2id. The metadata (curve, public key, creation time and KDF parameters) is stored in
+//! the clear and authenticated as associated data, so it cannot be swapped without detection.
+
+use crate::an23_proxy_signature::{Parameters, SigningKey, VerificationKey};
+use crate::{Error, NamedCurve};
+use argon2::{Algorithm, Argon2, Params, Version};
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+const KEYSTORE_VERSION: u8 = 1;
+
+/// Argon2id cost parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KdfParams {
+    /// Memory size in KiB.
+    pub m_cost: u32,
+    /// Number of iterations.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// The most expensive parameters a keystore may ask for: 1 GiB, 16 iterations, 16 lanes.
+    /// They are checked before deriving a key, so a crafted file cannot exhaust memory or time.
+    pub const MAX: Self = Self {
+        m_cost: 1024 * 1024,
+        t_cost: 16,
+        p_cost: 16,
+    };
+
+    fn check(&self) -> Result<(), Error> {
+        if self.m_cost > Self::MAX.m_cost
+            || self.t_cost > Self::MAX.t_cost
+            || self.p_cost > Self::MAX.p_cost
+        {
+            return Err(Error::SerializationFailed);
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    /// The OWASP recommendation for Argon2id: 19 MiB, 2 iterations, 1 lane.
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+struct KeystoreHeader {
+    version: u8,
+    curve: String,
+    /// Compressed verification key.
+    public_key: Vec<u8>,
+    /// Unix time in seconds.
+    created_at: u64,
+    kdf_params: KdfParams,
+    salt: [u8; 16],
+    nonce: [u8; 24],
+}
+
+/// A signing key encrypted with a password.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Keystore {
+    header: KeystoreHeader,
+    ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    /// Encrypts `sk` with the default KDF parameters.
+    pub fn encrypt<G, R>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        sk: &SigningKey<G>,
+        password: &[u8],
+        created_at: u64,
+    ) -> Result<Self, Error>
+    where
+        G: CurveGroup + NamedCurve,
+        G::ScalarField: PrimeField,
+        R: Rng,
+    {
+        Self::encrypt_with_params(
+            rng,
+            parameters,
+            sk,
+            password,
+            created_at,
+            KdfParams::default(),
+        )
+    }
+
+    pub fn encrypt_with_params<G, R>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        sk: &SigningKey<G>,
+        password: &[u8],
+        created_at: u64,
+        kdf_params: KdfParams,
+    ) -> Result<Self, Error>
+    where
+        G: CurveGroup + NamedCurve,
+        G::ScalarField: PrimeField,
+        R: Rng,
+    {
+        let vk: VerificationKey<G> = parameters.mul_generator(sk.0).into();
+        let mut public_key = Vec::new();
+        vk.serialize_compressed(&mut public_key)
+            .map_err(|_| Error::SerializationFailed)?;
+
+        let mut plaintext = Zeroizing::new(Vec::new());
+        sk.serialize_compressed(&mut *plaintext)
+            .map_err(|_| Error::SerializationFailed)?;
+
+        let header = KeystoreHeader {
+            version: KEYSTORE_VERSION,
+            curve: G::NAME.to_string(),
+            public_key,
+            created_at,
+            kdf_params,
+            salt: rng.r#gen(),
+            nonce: rng.r#gen(),
+        };
+        let ciphertext = header.cipher(password)?.encrypt(
+            XNonce::from_slice(&header.nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &header.associated_data()?,
+            },
+        );
+
+        Ok(Self {
+            header,
+            ciphertext: ciphertext.map_err(|_| Error::SerializationFailed)?,
+        })
+    }
+
+    /// Decrypts the signing key. Fails with [`Error::InvalidPassword`] if the password is wrong
+    /// or the keystore was tampered with, and with [`Error::SerializationFailed`] if its KDF
+    /// parameters exceed [`KdfParams::MAX`].
+    pub fn decrypt<G>(
+        &self,
+        parameters: &Parameters<G>,
+        password: &[u8],
+    ) -> Result<SigningKey<G>, Error>
+    where
+        G: CurveGroup + NamedCurve,
+        G::ScalarField: PrimeField,
+    {
+        let vk = self.verification_key::<G>()?;
+
+        let plaintext = self.header.decrypt(password, &self.ciphertext)?;
+        let sk = SigningKey::<G>::deserialize_compressed(&plaintext[..])
+            .map_err(|_| Error::SerializationFailed)?;
+
+        if parameters.mul_generator(sk.0).into_affine() != vk {
+            return Err(Error::InvalidKeystore); // Encrypted for different parameters
+        }
+
+        Ok(sk)
+    }
+
+    /// Re-encrypts the signing key under `new_password`, with a fresh salt and nonce. The
+    /// metadata is kept.
+    pub fn change_password<R: Rng>(
+        &self,
+        rng: &mut R,
+        old_password: &[u8],
+        new_password: &[u8],
+    ) -> Result<Self, Error> {
+        let plaintext = self.header.decrypt(old_password, &self.ciphertext)?;
+
+        let header = KeystoreHeader {
+            salt: rng.r#gen(),
+            nonce: rng.r#gen(),
+            ..self.header.clone()
+        };
+        let ciphertext = header.cipher(new_password)?.encrypt(
+            XNonce::from_slice(&header.nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &header.associated_data()?,
+            },
+        );
+
+        Ok(Self {
+            header,
+            ciphertext: ciphertext.map_err(|_| Error::SerializationFailed)?,
+        })
+    }
+
+    /// Name of the curve of the encrypted key, see [`NamedCurve`].
+    pub fn curve(&self) -> &str {
+        &self.header.curve
+    }
+
+    /// Unix time in seconds at which the keystore was created.
+    pub fn created_at(&self) -> u64 {
+        self.header.created_at
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.header.kdf_params
+    }
+
+    /// The verification key of the encrypted signing key, readable without the password.
+    pub fn verification_key<G>(&self) -> Result<VerificationKey<G>, Error>
+    where
+        G: CurveGroup + NamedCurve,
+    {
+        if self.header.curve != G::NAME {
+            return Err(Error::InvalidKeystore);
+        }
+        VerificationKey::<G>::deserialize_compressed(&*self.header.public_key)
+            .map_err(|_| Error::SerializationFailed)
+    }
+}
+
+impl KeystoreHeader {
+    fn cipher(&self, password: &[u8]) -> Result<XChaCha20Poly1305, Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::InvalidKeystore);
+        }
+
+        self.kdf_params.check()?;
+        let params = Params::new(
+            self.kdf_params.m_cost,
+            self.kdf_params.t_cost,
+            self.kdf_params.p_cost,
+            Some(32),
+        )
+        .map_err(|_| Error::InvalidKeystore)?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password, &self.salt, &mut *key)
+            .map_err(|_| Error::InvalidKeystore)?;
+
+        Ok(XChaCha20Poly1305::new((&*key).into()))
+    }
+
+    fn decrypt(&self, password: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        self.cipher(password)?
+            .decrypt(
+                XNonce::from_slice(&self.nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &self.associated_data()?,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| Error::InvalidPassword)
+    }
+
+    fn associated_data(&self) -> Result<Vec<u8>, Error> {
+        let mut aad = Vec::new();
+        self.serialize_compressed(&mut aad)
+            .map_err(|_| Error::SerializationFailed)?;
+        Ok(aad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::AN23ProxySignature;
+    use ark_grumpkin::Projective;
+    use ark_std::test_rng;
+
+    /// Cheap parameters, to keep the tests fast.
+    const TEST_KDF_PARAMS: KdfParams = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let keystore = Keystore::encrypt_with_params(
+            &mut rng,
+            &parameters,
+            &sk,
+            b"correct horse",
+            1_750_000_000,
+            TEST_KDF_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(keystore.curve(), "grumpkin");
+        assert_eq!(keystore.created_at(), 1_750_000_000);
+        assert_eq!(keystore.verification_key::<Projective>().unwrap(), vk);
+
+        let mut bytes = Vec::new();
+        keystore.serialize_compressed(&mut bytes).unwrap();
+        let keystore = Keystore::deserialize_compressed(&*bytes).unwrap();
+
+        let mut decrypted = keystore.decrypt(&parameters, b"correct horse").unwrap();
+        assert_eq!(decrypted.0, sk.0);
+        // The key never shows up in logs, and is wiped on drop
+        assert_eq!(format!("{decrypted:?}"), "SigningKey(<redacted>)");
+        zeroize::Zeroize::zeroize(&mut decrypted);
+        assert_eq!(decrypted.0, Default::default());
+        assert_eq!(
+            keystore
+                .decrypt(&parameters, b"battery staple")
+                .unwrap_err(),
+            Error::InvalidPassword
+        );
+
+        let keystore = keystore
+            .change_password(&mut rng, b"correct horse", b"battery staple")
+            .unwrap();
+        assert_eq!(keystore.created_at(), 1_750_000_000);
+        assert_eq!(
+            keystore.decrypt(&parameters, b"correct horse").unwrap_err(),
+            Error::InvalidPassword
+        );
+        assert_eq!(
+            keystore.decrypt(&parameters, b"battery staple").unwrap().0,
+            sk.0
+        );
+    }
+
+    #[test]
+    fn test_keystore_metadata_is_authenticated() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let mut keystore = Keystore::encrypt_with_params(
+            &mut rng,
+            &parameters,
+            &sk,
+            b"correct horse",
+            1_750_000_000,
+            TEST_KDF_PARAMS,
+        )
+        .unwrap();
+        keystore.header.created_at += 1;
+
+        assert_eq!(
+            keystore.decrypt(&parameters, b"correct horse").unwrap_err(),
+            Error::InvalidPassword
+        );
+    }
+
+    #[test]
+    fn test_keystore_kdf_params_are_bounded() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+
+        let mut keystore = Keystore::encrypt_with_params(
+            &mut rng,
+            &parameters,
+            &sk,
+            b"correct horse",
+            1_750_000_000,
+            TEST_KDF_PARAMS,
+        )
+        .unwrap();
+        // A file asking for 4 TiB must be rejected before any memory is allocated
+        keystore.header.kdf_params.m_cost = u32::MAX;
+        assert_eq!(
+            keystore.decrypt(&parameters, b"correct horse").unwrap_err(),
+            Error::SerializationFailed
+        );
+        keystore.header.kdf_params = KdfParams {
+            t_cost: KdfParams::MAX.t_cost + 1,
+            ..TEST_KDF_PARAMS
+        };
+        assert_eq!(
+            keystore
+                .change_password(&mut rng, b"correct horse", b"battery staple")
+                .unwrap_err(),
+            Error::SerializationFailed
+        );
+        assert_eq!(
+            Keystore::encrypt_with_params(
+                &mut rng,
+                &parameters,
+                &sk,
+                b"correct horse",
+                1_750_000_000,
+                KdfParams {
+                    p_cost: KdfParams::MAX.p_cost + 1,
+                    ..TEST_KDF_PARAMS
+                },
+            )
+            .unwrap_err(),
+            Error::SerializationFailed
+        );
+    }
+}
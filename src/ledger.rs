@@ -0,0 +1,236 @@
+//! Delegator-side reconciliation of issued, spent and revoked tokens.
+//!
+//! Tokens are identified by their `m0`, which is also what the delegator keeps as revocation key
+//! and what verifiers record in their revocation state. Revoking a delegation adds all of its
+//! `m0`s to the revocation state, so entries of a delegation that is already recorded as revoked
+//! count as revoked rather than spent: import verifier states before recording a revocation.
+
+use crate::Error;
+use ark_ff::PrimeField;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+struct DelegationEntry {
+    id: String,
+    issued: u64,
+    spent: u64,
+    revoked: bool,
+}
+
+#[derive(Default)]
+pub struct Ledger<F: PrimeField> {
+    delegations: Vec<DelegationEntry>,
+    /// `m0` -> index into `delegations`.
+    tokens: HashMap<F, usize>,
+    spent: HashSet<F>,
+    unknown: HashSet<F>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DelegationReport {
+    pub id: String,
+    pub issued: u64,
+    pub spent: u64,
+    /// Tokens that were revoked before being spent.
+    pub revoked: u64,
+    pub outstanding: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LedgerReport {
+    pub delegations: Vec<DelegationReport>,
+    /// Revocation state entries that do not belong to any imported delegation.
+    pub unknown_entries: u64,
+}
+
+impl<F: PrimeField> Ledger<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Imports the revocation key returned by `delegate` under a delegator-chosen identifier.
+    /// Repeated `m0`s in `rev_key` are counted once.
+    pub fn import_issuance(&mut self, id: impl Into<String>, rev_key: &[F]) -> Result<(), Error> {
+        let id = id.into();
+        let m0s = rev_key.iter().copied().collect::<HashSet<_>>();
+        if self.delegations.iter().any(|entry| entry.id == id)
+            || m0s.iter().any(|m0| self.tokens.contains_key(m0))
+        {
+            return Err(Error::DuplicateDelegation);
+        }
+
+        let index = self.delegations.len();
+        self.tokens.extend(m0s.iter().map(|&m0| (m0, index)));
+        self.delegations.push(DelegationEntry {
+            id,
+            issued: m0s.len() as u64,
+            spent: 0,
+            revoked: false,
+        });
+
+        // Entries observed before the issuance was imported
+        for m0 in m0s {
+            if self.unknown.remove(&m0) {
+                self.mark_spent(m0);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Imports the entries of a verifier's revocation state. Entries can be imported repeatedly,
+    /// e.g. from several verifiers or successive snapshots.
+    pub fn import_revocation_state(&mut self, rev_state: &[F]) {
+        for &m0 in rev_state {
+            match self.tokens.get(&m0) {
+                Some(&index) if self.delegations[index].revoked => {}
+                Some(_) => self.mark_spent(m0),
+                None => {
+                    self.unknown.insert(m0);
+                }
+            }
+        }
+    }
+
+    /// Records that the delegator revoked the delegation `id`.
+    pub fn record_revocation(&mut self, id: &str) -> Result<(), Error> {
+        let entry = self
+            .delegations
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or(Error::UnknownDelegation)?;
+        entry.revoked = true;
+        Ok(())
+    }
+
+    pub fn report(&self) -> LedgerReport {
+        let delegations = self
+            .delegations
+            .iter()
+            .map(|entry| {
+                let unspent = entry.issued - entry.spent;
+                let revoked = if entry.revoked { unspent } else { 0 };
+                DelegationReport {
+                    id: entry.id.clone(),
+                    issued: entry.issued,
+                    spent: entry.spent,
+                    revoked,
+                    outstanding: unspent - revoked,
+                }
+            })
+            .collect();
+
+        LedgerReport {
+            delegations,
+            unknown_entries: self.unknown.len() as u64,
+        }
+    }
+
+    fn mark_spent(&mut self, m0: F) {
+        if self.spent.insert(m0) {
+            self.delegations[self.tokens[&m0]].spent += 1;
+        }
+    }
+}
+
+impl LedgerReport {
+    /// One line per delegation, with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id,issued,spent,revoked,outstanding\n");
+        for d in &self.delegations {
+            let id = if d.id.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", d.id.replace('"', "\"\""))
+            } else {
+                d.id.clone()
+            };
+            writeln!(
+                csv,
+                "{id},{},{},{},{}",
+                d.issued, d.spent, d.revoked, d.outstanding
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("reports serialize to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_grumpkin::Fr;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_ledger_reconciliation() {
+        let mut rng = test_rng();
+        let alice = (0..4).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let bob = (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let foreign = Fr::rand(&mut rng);
+
+        let mut ledger = Ledger::new();
+        // A repeated token is issued once
+        ledger
+            .import_issuance("alice", &[alice.as_slice(), &alice[..1]].concat())
+            .unwrap();
+        assert_eq!(
+            ledger.import_issuance("alice", &bob),
+            Err(Error::DuplicateDelegation)
+        );
+
+        // Bob spends one token before his issuance record is imported
+        ledger.import_revocation_state(&[alice[0], alice[1], bob[0], foreign]);
+        ledger.import_issuance("bob, sales", &bob).unwrap();
+        ledger.import_revocation_state(&[alice[1]]);
+
+        // Revoking Bob's delegation puts all of his tokens in the revocation state
+        ledger.record_revocation("bob, sales").unwrap();
+        ledger.import_revocation_state(&bob);
+        assert_eq!(
+            ledger.record_revocation("carol"),
+            Err(Error::UnknownDelegation)
+        );
+
+        let report = ledger.report();
+        assert_eq!(
+            report.delegations,
+            vec![
+                DelegationReport {
+                    id: "alice".to_string(),
+                    issued: 4,
+                    spent: 2,
+                    revoked: 0,
+                    outstanding: 2,
+                },
+                DelegationReport {
+                    id: "bob, sales".to_string(),
+                    issued: 3,
+                    spent: 1,
+                    revoked: 2,
+                    outstanding: 0,
+                },
+            ]
+        );
+        assert_eq!(report.unknown_entries, 1);
+
+        assert_eq!(
+            report.to_csv(),
+            "id,issued,spent,revoked,outstanding\nalice,4,2,0,2\n\"bob, sales\",3,1,2,0\n"
+        );
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            report.to_json(),
+            "{\"delegations\":[\
+             {\"id\":\"alice\",\"issued\":4,\"spent\":2,\"revoked\":0,\"outstanding\":2},\
+             {\"id\":\"bob, sales\",\"issued\":3,\"spent\":1,\"revoked\":2,\"outstanding\":0}\
+             ],\"unknown_entries\":1}"
+        );
+    }
+}
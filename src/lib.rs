@@ -4,8 +4,32 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::Rng;
 
 pub mod an23_proxy_signature;
+pub mod aztec;
+pub mod challenge_hash;
+pub mod curves;
+pub mod delegation_stream;
+pub mod fixed_base;
+pub mod key_derivation;
+pub mod keystore;
+pub mod ledger;
 pub mod noir_utils;
+pub mod poseidon2;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
+#[cfg(feature = "revocation-db")]
+pub mod revocation_db;
+pub mod revocation_store;
+#[cfg(feature = "r1cs")]
+pub mod revocation_tree;
+#[cfg(feature = "serde")]
+mod serde_utils;
+#[cfg(any(feature = "secp256k1", feature = "bn254"))]
+pub mod solidity;
+pub mod typed_data;
+pub mod wallet;
 pub mod wasm_bindings;
+#[cfg(feature = "zk")]
+pub mod zk;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -15,6 +39,52 @@ pub enum Error {
     DelegationFailed,
     UseOfRevokedToken,
     NoDelegationToken,
+    ExtractionFailed,
+    SerializationFailed,
+    StorageFailed,
+    DelegationExpired,
+    DuplicateDelegation,
+    UnknownDelegation,
+    InvalidPassword,
+    InvalidKeystore,
+    InvalidMnemonic,
+    DerivationFailed,
+    InvalidTypedData,
+    ProofFailed,
+}
+
+/// A stable, human-readable name for a curve, recorded alongside serialized keys.
+pub trait NamedCurve {
+    const NAME: &'static str;
+}
+
+impl NamedCurve for ark_grumpkin::Projective {
+    const NAME: &'static str = "grumpkin";
+}
+
+#[cfg(feature = "secp256k1")]
+impl NamedCurve for curves::secp256k1::Projective {
+    const NAME: &'static str = "secp256k1";
+}
+
+#[cfg(feature = "bn254")]
+impl NamedCurve for curves::Bn254 {
+    const NAME: &'static str = "bn254";
+}
+
+#[cfg(feature = "pasta")]
+impl NamedCurve for curves::pasta::pallas::Projective {
+    const NAME: &'static str = "pallas";
+}
+
+#[cfg(feature = "pasta")]
+impl NamedCurve for curves::pasta::vesta::Projective {
+    const NAME: &'static str = "vesta";
+}
+
+#[cfg(feature = "ristretto")]
+impl NamedCurve for curves::ristretto::Projective {
+    const NAME: &'static str = "ristretto";
 }
 
 /// Interface for a proxy signature scheme as defined in [AN23](https://eprint.iacr.org/2023/833).
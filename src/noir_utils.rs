@@ -1,22 +1,219 @@
-use crate::an23_proxy_signature::Signature;
-use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+//! Feeding Rust values into the Noir verifier in `verifier/src`.
+//!
+//! [`ToNoir`] renders a value either as a Noir expression, to paste into tests, or as a Noir ABI
+//! input value, the JSON that `noir_js` takes as circuit inputs. Grumpkin scalars become
+//! `EmbeddedCurveScalar`s, points `EmbeddedCurvePoint`s, and base field elements `Field`s.
+
+use crate::an23_proxy_signature::{
+    AN23ProxySignature, DelegationSpec, Policy, Sigma, Signature, SigningToken, Theta,
+    VerificationKey,
+};
+use crate::aztec::{TransferIntent, field_to_hex, split_scalar};
+#[cfg(feature = "r1cs")]
+use crate::revocation_tree::NonMembershipProof;
+use crate::{Error, ProxySignature};
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_grumpkin::{Affine, Fq, Fr, Projective};
+use rand::Rng;
+
+/// A value with a counterpart in the Noir verifier.
+pub trait ToNoir {
+    /// A Noir expression constructing the value.
+    fn to_noir(&self) -> String;
+
+    /// The value as a Noir ABI input: fields and integers are strings, structs are objects.
+    fn to_noir_abi(&self) -> String;
+}
+
+impl ToNoir for Fr {
+    fn to_noir(&self) -> String {
+        let [lo, hi] = split_scalar(*self);
+        format!(
+            "EmbeddedCurveScalar::new(0x{}, 0x{})",
+            hex_digits(&lo),
+            hex_digits(&hi)
+        )
+    }
+
+    fn to_noir_abi(&self) -> String {
+        let [lo, hi] = split_scalar(*self);
+        abi_object(&[("lo", lo.to_noir_abi()), ("hi", hi.to_noir_abi())])
+    }
+}
+
+impl ToNoir for Fq {
+    fn to_noir(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_noir_abi(&self) -> String {
+        format!("\"{}\"", field_to_hex(self))
+    }
+}
+
+impl ToNoir for Affine {
+    fn to_noir(&self) -> String {
+        let (x, y) = self.xy().unwrap_or_default();
+        format!(
+            "EmbeddedCurvePoint {{x: {x}, y: {y}, is_infinite: {}}}",
+            self.is_zero()
+        )
+    }
+
+    fn to_noir_abi(&self) -> String {
+        let (x, y) = self.xy().unwrap_or_default();
+        abi_object(&[
+            ("x", x.to_noir_abi()),
+            ("y", y.to_noir_abi()),
+            ("is_infinite", self.is_zero().to_noir_abi()),
+        ])
+    }
+}
+
+impl ToNoir for Projective {
+    fn to_noir(&self) -> String {
+        self.into_affine().to_noir()
+    }
+
+    fn to_noir_abi(&self) -> String {
+        self.into_affine().to_noir_abi()
+    }
+}
+
+impl ToNoir for bool {
+    fn to_noir(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_noir_abi(&self) -> String {
+        self.to_string()
+    }
+}
+
+macro_rules! impl_to_noir_for_integer {
+    ($($t:ty),*) => {
+        $(impl ToNoir for $t {
+            fn to_noir(&self) -> String {
+                self.to_string()
+            }
+
+            fn to_noir_abi(&self) -> String {
+                format!("\"{self}\"")
+            }
+        })*
+    };
+}
+
+impl_to_noir_for_integer!(u8, u32, u64, u128);
+
+impl<T: ToNoir + Default> ToNoir for Option<T> {
+    fn to_noir(&self) -> String {
+        match self {
+            Some(value) => format!("Option::some({})", value.to_noir()),
+            None => "Option::none()".to_string(),
+        }
+    }
+
+    fn to_noir_abi(&self) -> String {
+        // Noir represents `None` with a default value behind `_is_some: false`
+        let value = self
+            .as_ref()
+            .map_or_else(|| T::default().to_noir_abi(), T::to_noir_abi);
+        abi_object(&[
+            ("_is_some", self.is_some().to_noir_abi()),
+            ("_value", value),
+        ])
+    }
+}
+
+impl<T: ToNoir> ToNoir for [T] {
+    fn to_noir(&self) -> String {
+        let values = self.iter().map(T::to_noir).collect::<Vec<_>>();
+        format!("[{}]", values.join(", "))
+    }
+
+    fn to_noir_abi(&self) -> String {
+        let values = self.iter().map(T::to_noir_abi).collect::<Vec<_>>();
+        format!("[{}]", values.join(","))
+    }
+}
+
+impl<T: ToNoir> ToNoir for Vec<T> {
+    fn to_noir(&self) -> String {
+        self.as_slice().to_noir()
+    }
+
+    fn to_noir_abi(&self) -> String {
+        self.as_slice().to_noir_abi()
+    }
+}
+
+/// Implements [`ToNoir`] for a struct with a Noir counterpart of the same name and fields.
+macro_rules! impl_to_noir_for_struct {
+    ([$($generics:tt)*] $t:ty, [$($bounds:tt)*] => $name:literal { $($field:ident),* }) => {
+        impl<$($generics)*> ToNoir for $t where $($bounds)* {
+            fn to_noir(&self) -> String {
+                noir_struct($name, &[$((stringify!($field), self.$field.to_noir())),*])
+            }
+
+            fn to_noir_abi(&self) -> String {
+                abi_object(&[$((stringify!($field), self.$field.to_noir_abi())),*])
+            }
+        }
+    };
+}
+
+impl_to_noir_for_struct!([F: Field + ToNoir] Sigma<F>, [] => "Sigma" { c0, c1, z1 });
+impl_to_noir_for_struct!(
+    [G: CurveGroup + ToNoir] Theta<G>, [G::ScalarField: ToNoir] => "Theta" { m0, Z0 }
+);
+impl_to_noir_for_struct!(
+    [G: CurveGroup + ToNoir] Signature<G>, [G::ScalarField: ToNoir] => "Signature" { sigma, theta }
+);
+impl_to_noir_for_struct!(
+    [G: CurveGroup] SigningToken<G>, [G::ScalarField: ToNoir + Default] => "SigningToken" {
+        z0, c0, m0, r1
+    }
+);
+impl_to_noir_for_struct!([] Policy, [] => "Policy" { amount });
+impl_to_noir_for_struct!([] TransferIntent, [] => "TransferIntent" {
+    token_contract, to, amount, nonce
+});
+#[cfg(feature = "r1cs")]
+impl_to_noir_for_struct!([] NonMembershipProof, [] => "NonMembershipProof" {
+    index, low, high, path
+});
+
+fn noir_struct(name: &str, fields: &[(&str, String)]) -> String {
+    let fields = fields
+        .iter()
+        .map(|(field, value)| format!("{field}: {value}"))
+        .collect::<Vec<_>>();
+    format!("{name} {{ {} }}", fields.join(", "))
+}
+
+fn abi_object(fields: &[(&str, String)]) -> String {
+    let fields = fields
+        .iter()
+        .map(|(field, value)| format!("\"{field}\":{value}"))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Minimal hex digits of a field element, e.g. `2a`.
+fn hex_digits(field: &Fq) -> String {
+    let hex = field_to_hex(field);
+    let digits = hex[2..].trim_start_matches('0');
+    if digits.is_empty() { "0" } else { digits }.to_string()
+}
 
 pub fn grumpkin_fr_to_nr_code(fr: ark_grumpkin::Fr) -> String {
-    let bi = fr.into_bigint().0;
-    let lo: u128 = (bi[0] as u128) | (bi[1] as u128) << 64;
-    let hi: u128 = (bi[2] as u128) | (bi[3] as u128) << 64;
-    format!("EmbeddedCurveScalar::new(0x{:x}, 0x{:x})", lo, hi)
+    fr.to_noir()
 }
 
 pub fn grumpkin_point_to_nr_code(point: ark_grumpkin::Projective) -> String {
-    let aff = point.into_affine();
-    format!(
-        "EmbeddedCurvePoint {{x: {}, y: {}, is_infinite: false}}",
-        aff.x().unwrap(),
-        aff.y().unwrap()
-    )
-    .to_string()
+    point.to_noir()
 }
 
 pub fn grumpkin_sig_to_nr_code(signature: &Signature<ark_grumpkin::Projective>) -> String {
@@ -48,3 +245,286 @@ pub fn grumpkin_sig_to_nr_code(signature: &Signature<ark_grumpkin::Projective>)
     s.push_str("    let sig = Signature { sigma, theta };\n");
     s
 }
+
+/// A signature to check with the Noir verifier, and whether `verify` should accept it.
+#[derive(Clone, Debug)]
+pub struct NoirVector {
+    pub name: String,
+    pub verification_key: VerificationKey<Projective>,
+    pub message: Fr,
+    pub signature: Signature<Projective>,
+    pub valid: bool,
+}
+
+/// `count` random signatures, half of them delegated, followed by edge cases and invalid
+/// signatures: a signature on another message, under another key or the identity, with each
+/// component tampered with, and with each component zero. Grumpkin has prime order, so the
+/// identity is its only low-order point.
+pub fn noir_vectors<R: Rng>(rng: &mut R, count: usize) -> Result<Vec<NoirVector>, Error> {
+    type Scheme = AN23ProxySignature<Projective>;
+
+    let parameters = Scheme::setup(rng)?;
+    let (sk, vk) = Scheme::keygen(rng, &parameters)?;
+    let (mut tokens, _) = Scheme::delegate(
+        rng,
+        &parameters,
+        &sk,
+        &DelegationSpec {
+            number_of_tokens: count as u64 / 2 + 3,
+            bind_message_nonce: false,
+        },
+    )?;
+
+    let mut vectors = Vec::new();
+    let mut push = |name: String, message: Fr, signature: Signature<Projective>, valid| {
+        vectors.push(NoirVector {
+            name,
+            verification_key: vk,
+            message,
+            signature,
+            valid,
+        })
+    };
+    for i in 0..count {
+        let message = Fr::rand(rng);
+        if i % 2 == 0 {
+            let signature = Scheme::sign(rng, &parameters, &sk, &message, None)?;
+            push(format!("valid_signature_{i}"), message, signature, true);
+        } else {
+            let signature = Scheme::delegated_sign(rng, &parameters, &mut tokens, &message)?;
+            push(
+                format!("valid_delegated_signature_{i}"),
+                message,
+                signature,
+                true,
+            );
+        }
+    }
+    for (name, message) in [("zero_message", Fr::zero()), ("max_message", -Fr::one())] {
+        let signature = Scheme::delegated_sign(rng, &parameters, &mut tokens, &message)?;
+        push(format!("valid_{name}"), message, signature, true);
+    }
+
+    let message = Fr::rand(rng);
+    let signature = Scheme::delegated_sign(rng, &parameters, &mut tokens, &message)?;
+    push(
+        "invalid_message".to_string(),
+        message + Fr::one(),
+        signature.clone(),
+        false,
+    );
+    let tamper = |f: fn(&mut Signature<Projective>)| {
+        let mut signature = signature.clone();
+        f(&mut signature);
+        signature
+    };
+    for (name, tampered) in [
+        ("c0", tamper(|s| s.sigma.c0 += Fr::one())),
+        ("c1", tamper(|s| s.sigma.c1 += Fr::one())),
+        ("z1", tamper(|s| s.sigma.z1 += Fr::one())),
+        ("m0", tamper(|s| s.theta.m0 += Fr::one())),
+        ("z0", tamper(|s| s.theta.Z0 += Projective::generator())),
+        ("zero_c0", tamper(|s| s.sigma.c0 = Fr::zero())),
+        ("zero_c1", tamper(|s| s.sigma.c1 = Fr::zero())),
+        ("zero_z1", tamper(|s| s.sigma.z1 = Fr::zero())),
+        ("zero_m0", tamper(|s| s.theta.m0 = Fr::zero())),
+        ("identity_z0", tamper(|s| s.theta.Z0 = Projective::zero())),
+    ] {
+        push(format!("invalid_{name}"), message, tampered, false);
+    }
+
+    let (_, other_vk) = Scheme::keygen(rng, &parameters)?;
+    for (name, verification_key) in [
+        ("verification_key", other_vk),
+        ("identity_verification_key", ark_grumpkin::Affine::zero()),
+    ] {
+        vectors.push(NoirVector {
+            name: format!("invalid_{name}"),
+            verification_key,
+            message,
+            signature: signature.clone(),
+            valid: false,
+        });
+    }
+    Ok(vectors)
+}
+
+/// A Noir module with one test per vector. Invalid vectors are `#[test(should_fail)]`.
+/// `arguments` are those `generate-noir-tests` was run with, recorded in the header so the module
+/// can be regenerated.
+pub fn noir_test_module(vectors: &[NoirVector], arguments: &str) -> String {
+    let mut s = String::new();
+    s.push_str(&format!(
+        "// Generated by `schnorr-tokens generate-noir-tests {arguments}`, do not edit.\n\n"
+    ));
+    s.push_str("use crate::schnorr_token::{Sigma, Signature, Theta, verify};\n");
+    s.push_str("use std::embedded_curve_ops::{EmbeddedCurvePoint, EmbeddedCurveScalar};\n");
+    for vector in vectors {
+        s.push_str(if vector.valid {
+            "\n#[test]\n"
+        } else {
+            "\n#[test(should_fail)]\n"
+        });
+        s.push_str(&format!("fn test_{}() {{\n", vector.name));
+        s.push_str(&format!(
+            "    let vk = {};\n",
+            grumpkin_point_to_nr_code(vector.verification_key.into())
+        ));
+        s.push_str(&format!(
+            "    let msg = {};\n",
+            grumpkin_fr_to_nr_code(vector.message)
+        ));
+        s.push_str(&grumpkin_sig_to_nr_code(&vector.signature));
+        s.push_str("    verify(msg, sig, vk);\n}\n");
+    }
+    s
+}
+
+/// `Prover.toml` inputs `msg`, `sig` and `vk` for the `main` of the circuit/ package, which
+/// takes the same arguments as `verify`.
+pub fn prover_toml(vector: &NoirVector) -> String {
+    let scalar = |name: &str, fr: Fr| {
+        let [lo, hi] = split_scalar(fr);
+        format!(
+            "[{name}]\nlo = \"{}\"\nhi = \"{}\"\n\n",
+            field_to_hex(&lo),
+            field_to_hex(&hi)
+        )
+    };
+    let point = |name: &str, point: ark_grumpkin::Affine| {
+        let (x, y) = point.xy().unwrap_or_default();
+        format!(
+            "[{name}]\nx = \"{}\"\ny = \"{}\"\nis_infinite = {}\n\n",
+            field_to_hex(&x),
+            field_to_hex(&y),
+            point.is_zero()
+        )
+    };
+    let signature = &vector.signature;
+    let mut s = String::new();
+    s.push_str(&scalar("msg", vector.message));
+    s.push_str(&scalar("sig.sigma.c0", signature.sigma.c0));
+    s.push_str(&scalar("sig.sigma.c1", signature.sigma.c1));
+    s.push_str(&scalar("sig.sigma.z1", signature.sigma.z1));
+    s.push_str(&scalar("sig.theta.m0", signature.theta.m0));
+    s.push_str(&point("sig.theta.Z0", signature.theta.Z0.into_affine()));
+    s.push_str(&point("vk", vector.verification_key));
+    s.truncate(s.len() - 1);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_noir_vectors() {
+        let vectors = noir_vectors(&mut test_rng(), 4).unwrap();
+        assert_eq!(vectors.len(), 4 + 2 + 13);
+
+        // The expectations agree with the Rust verifier
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut test_rng()).unwrap();
+        for vector in &vectors {
+            let verified = AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vector.verification_key,
+                &vector.message,
+                &vector.signature,
+                &mut Vec::new(),
+            )
+            .unwrap();
+            assert_eq!(verified, vector.valid, "{}", vector.name);
+        }
+
+        let module = noir_test_module(&vectors, "--count 4 --seed 0");
+        assert!(module.starts_with(
+            "// Generated by `schnorr-tokens generate-noir-tests --count 4 --seed 0`, do not edit."
+        ));
+        assert_eq!(module.matches("#[test]").count(), 6);
+        assert_eq!(module.matches("#[test(should_fail)]").count(), 13);
+        assert!(module.contains("fn test_invalid_verification_key() {"));
+        assert!(module.contains("fn test_invalid_identity_z0() {"));
+
+        let toml = prover_toml(&vectors[0]);
+        assert!(toml.starts_with("[msg]\nlo = \"0x"));
+        assert!(toml.contains("[sig.theta.Z0]\nx = \"0x"));
+        assert!(toml.ends_with("is_infinite = false\n"));
+        let toml = prover_toml(vectors.last().unwrap());
+        assert!(toml.contains("[vk]\nx = \"0x00"));
+        assert!(toml.ends_with("is_infinite = true\n"));
+    }
+
+    #[test]
+    fn test_to_noir() {
+        let scalar = Fr::from(u128::MAX) + Fr::from(0x2bu64);
+        assert_eq!(scalar.to_noir(), "EmbeddedCurveScalar::new(0x2a, 0x1)");
+        assert_eq!(
+            Projective::zero().to_noir(),
+            "EmbeddedCurvePoint {x: 0, y: 0, is_infinite: true}"
+        );
+        assert_eq!(None::<Fr>.to_noir(), "Option::none()");
+        assert_eq!(Policy { amount: 5 }.to_noir(), "Policy { amount: 5 }");
+
+        let intent = TransferIntent {
+            to: Fq::from(0x2au64),
+            amount: 10,
+            nonce: Fq::from(7u64),
+            token_contract: Fq::from(0x1234u64),
+        };
+        assert_eq!(
+            intent.to_noir(),
+            "TransferIntent { token_contract: 4660, to: 42, amount: 10, nonce: 7 }"
+        );
+
+        let vectors = noir_vectors(&mut test_rng(), 1).unwrap();
+        let signature = &vectors[0].signature;
+        assert!(signature.to_noir().starts_with(&format!(
+            "Signature {{ sigma: Sigma {{ c0: {}, ",
+            signature.sigma.c0.to_noir()
+        )));
+
+        // ABI inputs are valid JSON with the shape of the Noir types
+        let abi: serde_json::Value = serde_json::from_str(&signature.to_noir_abi()).unwrap();
+        let [lo, hi] = split_scalar(signature.theta.m0);
+        assert_eq!(abi["theta"]["m0"]["lo"], field_to_hex(&lo));
+        assert_eq!(abi["theta"]["m0"]["hi"], field_to_hex(&hi));
+        assert_eq!(abi["theta"]["Z0"]["is_infinite"], false);
+
+        let token = SigningToken::<Projective> {
+            z0: Fr::one(),
+            c0: Fr::one(),
+            m0: Fr::one(),
+            r1: None,
+        };
+        let abi: serde_json::Value = serde_json::from_str(&token.to_noir_abi()).unwrap();
+        assert_eq!(abi["r1"]["_is_some"], false);
+        let abi: serde_json::Value = serde_json::from_str(&intent.to_noir_abi()).unwrap();
+        assert_eq!(abi["amount"], "10");
+        assert_eq!(abi["to"], field_to_hex(&intent.to));
+
+        #[cfg(feature = "r1cs")]
+        {
+            use crate::revocation_store::RevocationStore;
+            use crate::revocation_tree::RevocationTree;
+
+            let mut tree = RevocationTree::new(2).unwrap();
+            tree.insert_if_absent(&Fr::from(5u64)).unwrap();
+            let proof = tree.non_membership_proof(&Fr::from(7u64)).unwrap();
+            assert_eq!(
+                proof.to_noir(),
+                format!(
+                    "NonMembershipProof {{ index: 1, low: EmbeddedCurveScalar::new(0x5, 0x0), \
+                     high: {}, path: [{}, {}] }}",
+                    (-Fr::one()).to_noir(),
+                    proof.path[0],
+                    proof.path[1]
+                )
+            );
+            let abi: serde_json::Value = serde_json::from_str(&proof.to_noir_abi()).unwrap();
+            assert_eq!(abi["index"], "1");
+            assert_eq!(abi["low"]["lo"], field_to_hex(&Fq::from(5u64)));
+            assert_eq!(abi["path"][1], field_to_hex(&proof.path[1]));
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! The Poseidon2 hash over BN254 with state width 4, as in Barretenberg and Noir's
+//! `std::hash::poseidon2`.
+//!
+//! The round constants are generated with the Grain LFSR of the
+//! [Poseidon2 reference implementation](https://github.com/HorizenLabs/poseidon2), for 8 full
+//! and 56 partial rounds with `x^5` S-boxes.
+
+use ark_ff::{AdditiveGroup, BigInteger, Field, PrimeField, Zero};
+use ark_grumpkin::Fq;
+use std::sync::OnceLock;
+
+const WIDTH: usize = 4;
+const RATE: usize = 3;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 56;
+
+/// The diagonal of the internal matrix, minus the identity.
+const INTERNAL_DIAGONAL: [&str; WIDTH] = [
+    "10dc6e9c006ea38b04b1e03b4bd9490c0d03f98929ca1d7fb56821fd19d3b6e7",
+    "0c28145b6a44df3e0149b3d0a30b3bb599df9756d4dd9b84a86b38cfb45a740b",
+    "00544b8338791518b2c7645a50392798b21f75bb60e3596170067d00141cac15",
+    "222c01175718386f2e2e82eb122789e352e105a3b8fa852613bc534433ee428b",
+];
+
+struct Constants {
+    /// One constant per state element in full rounds, one for the first element in partial
+    /// rounds.
+    round_constants: Vec<Vec<Fq>>,
+    internal_diagonal: [Fq; WIDTH],
+}
+
+fn constants() -> &'static Constants {
+    static CONSTANTS: OnceLock<Constants> = OnceLock::new();
+    CONSTANTS.get_or_init(|| {
+        let mut lfsr = GrainLfsr::new(Fq::MODULUS_BIT_SIZE as usize);
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|round| {
+                let partial = (FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS).contains(&round);
+                let count = if partial { 1 } else { WIDTH };
+                (0..count).map(|_| lfsr.field_element()).collect()
+            })
+            .collect();
+        let internal_diagonal = INTERNAL_DIAGONAL.map(|hex| {
+            let mut bytes = hex::decode(hex).expect("valid constant");
+            bytes.reverse();
+            Fq::from_le_bytes_mod_order(&bytes)
+        });
+        Constants {
+            round_constants,
+            internal_diagonal,
+        }
+    })
+}
+
+/// The Poseidon2 permutation, `poseidon2_permutation(state, 4)` in Noir.
+pub fn permutation(mut state: [Fq; WIDTH]) -> [Fq; WIDTH] {
+    let constants = constants();
+    external_matrix(&mut state);
+    for round_constants in &constants.round_constants {
+        if round_constants.len() == WIDTH {
+            for (x, c) in state.iter_mut().zip(round_constants) {
+                *x = sbox(*x + c);
+            }
+            external_matrix(&mut state);
+        } else {
+            state[0] = sbox(state[0] + round_constants[0]);
+            let sum = state.iter().sum::<Fq>();
+            for (x, d) in state.iter_mut().zip(&constants.internal_diagonal) {
+                *x = *x * d + sum;
+            }
+        }
+    }
+    state
+}
+
+/// `Poseidon2::hash(inputs, inputs.len())` in Noir: a sponge with rate 3 whose capacity element
+/// starts at `inputs.len() * 2^64`.
+pub fn hash(inputs: &[Fq]) -> Fq {
+    let mut state = [Fq::zero(); WIDTH];
+    state[RATE] = Fq::from(inputs.len() as u64) * Fq::from(u128::from(u64::MAX) + 1);
+    // An empty input is still permuted once, when squeezing
+    let mut chunks = inputs.chunks(RATE).peekable();
+    if chunks.peek().is_none() {
+        return permutation(state)[0];
+    }
+    for chunk in chunks {
+        for (x, input) in state.iter_mut().zip(chunk) {
+            *x += input;
+        }
+        state = permutation(state);
+    }
+    state[0]
+}
+
+fn sbox(x: Fq) -> Fq {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// Multiplication by the circulant-like 4x4 matrix of the Poseidon2 paper, in Barretenberg's
+/// order of additions.
+fn external_matrix(state: &mut [Fq; WIDTH]) {
+    let [x0, x1, x2, x3] = *state;
+    let t0 = x0 + x1;
+    let t1 = x2 + x3;
+    let t2 = x1.double() + t1;
+    let t3 = x3.double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    *state = [t3 + t5, t5, t2 + t4, t4];
+}
+
+/// The Grain LFSR of the Poseidon reference implementation, seeded with the parameters.
+struct GrainLfsr {
+    bits: [bool; 80],
+    field_bits: usize,
+}
+
+impl GrainLfsr {
+    fn new(field_bits: usize) -> Self {
+        let mut seed = Vec::with_capacity(80);
+        let mut push = |value: usize, width: usize| {
+            seed.extend((0..width).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        push(1, 2); // Prime field
+        push(0, 4); // x^alpha S-box
+        push(field_bits, 12);
+        push(WIDTH, 12);
+        push(FULL_ROUNDS, 10);
+        push(PARTIAL_ROUNDS, 10);
+        push((1 << 30) - 1, 30);
+
+        let mut lfsr = Self {
+            bits: seed.try_into().expect("80 bits"),
+            field_bits,
+        };
+        for _ in 0..160 {
+            lfsr.clock();
+        }
+        lfsr
+    }
+
+    fn clock(&mut self) -> bool {
+        let b = &self.bits;
+        let bit = b[62] ^ b[51] ^ b[38] ^ b[23] ^ b[13] ^ b[0];
+        self.bits.copy_within(1.., 0);
+        self.bits[79] = bit;
+        bit
+    }
+
+    /// Self-shrinking: a bit is output only if the bit before it is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// A uniform field element, by rejection sampling big-endian integers.
+    fn field_element(&mut self) -> Fq {
+        loop {
+            let mut bits = (0..self.field_bits)
+                .map(|_| self.next_bit())
+                .collect::<Vec<_>>();
+            bits.reverse();
+            let mut bytes = vec![0u8; self.field_bits.div_ceil(8)];
+            for (i, bit) in bits.iter().enumerate() {
+                bytes[i / 8] |= (*bit as u8) << (i % 8);
+            }
+            let element = Fq::from_le_bytes_mod_order(&bytes);
+            if element.into_bigint().to_bytes_le() == pad(&bytes) {
+                return element;
+            }
+        }
+    }
+}
+
+fn pad(bytes: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes.resize(32, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(hex: &str) -> Fq {
+        let mut bytes = hex::decode(hex).unwrap();
+        bytes.reverse();
+        Fq::from_le_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_permutation_vector() {
+        // Barretenberg's and Noir's test vector
+        let state = [0u64, 1, 2, 3].map(Fq::from);
+        assert_eq!(
+            permutation(state),
+            [
+                field("01bd538c2ee014ed5141b29e9ae240bf8db3fe5b9a38629a9647cf8d76c01737"),
+                field("239b62e7db98aa3a2a8f6a0d2fa1709e7a35959aa6c7034814d9daa90cbac662"),
+                field("04cbb44c61d928ed06808456bf758cbf0c18d1e15a7b6dbc8245fa7515d5e3cb"),
+                field("2e11c5cff2a22c64d01304b778d78f6998eff1ab73163a35603f54794c30847a"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! R1CS constraints for AN23 signature verification over Grumpkin.
+//!
+//! Grumpkin's base field is BN254's scalar field, so the constraints live over BN254 and can be
+//! proven with Groth16 over BN254. Blake2s is expensive in R1CS, so the signatures checked by
+//! [`verify_gadget`] use a Poseidon [`challenge`] in place of the Blake2s challenge: they are
+//! made by `AN23ProxySignature<Projective, Poseidon>` and [`verify`] is the native counterpart.
+//!
+//! Grumpkin scalars do not fit in a BN254 field element. In circuit they are 254 little-endian
+//! bits, and they are absorbed into Poseidon as their low and high 128 bits, like Noir's
+//! `EmbeddedCurveScalar`.
+
+use crate::an23_proxy_signature::{Signature, VerificationKey};
+use crate::aztec::split_scalar;
+use crate::challenge_hash::{ChallengeHash, ChallengeInput, field_elements, to_scalar};
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{
+    PoseidonConfig, PoseidonSponge, find_poseidon_ark_and_mds,
+};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ec::{CurveGroup, PrimeGroup};
+use ark_ff::PrimeField;
+use ark_grumpkin::constraints::GVar;
+use ark_grumpkin::{Affine, Fq, Fr, Projective};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
+use std::borrow::Borrow;
+use std::sync::OnceLock;
+
+/// Poseidon over BN254 with state width 3, 8 full and 57 partial rounds and `x^5` S-boxes.
+pub fn poseidon_config() -> PoseidonConfig<Fq> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fq>(Fq::MODULUS_BIT_SIZE as u64, 2, 8, 57, 0);
+    PoseidonConfig::new(8, 57, 5, mds, ark, 2, 1)
+}
+
+/// [`poseidon_config`], computed once.
+pub fn shared_poseidon_config() -> &'static PoseidonConfig<Fq> {
+    static CONFIG: OnceLock<PoseidonConfig<Fq>> = OnceLock::new();
+    CONFIG.get_or_init(poseidon_config)
+}
+
+/// The challenge `H(f, p, q)` of both signature layers: Poseidon over the limbs of `f` and the
+/// coordinates of `p` and `q`, see [`field_elements`].
+pub fn challenge(config: &PoseidonConfig<Fq>, f: &Fr, p: &Affine, q: &Affine) -> Fr {
+    poseidon(
+        config,
+        &[
+            ChallengeInput::Field(*f),
+            ChallengeInput::Curve(*p),
+            ChallengeInput::Curve(*q),
+        ],
+    )
+}
+
+fn poseidon(config: &PoseidonConfig<Fq>, inputs: &[ChallengeInput<Projective>]) -> Fr {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&field_elements(inputs));
+    to_scalar(sponge.squeeze_native_field_elements(1)[0])
+}
+
+/// Poseidon challenges, for signatures that [`verify_gadget`] accepts:
+/// `AN23ProxySignature<Projective, Poseidon>`.
+pub struct Poseidon;
+
+impl ChallengeHash<Projective> for Poseidon {
+    fn hash(inputs: &[ChallengeInput<Projective>]) -> Fr {
+        poseidon(shared_poseidon_config(), inputs)
+    }
+}
+
+/// Checks both challenge equations natively, the same statement as [`verify_gadget`].
+/// Revocation is up to the caller.
+pub fn verify(
+    config: &PoseidonConfig<Fq>,
+    vk: &VerificationKey<Projective>,
+    message: &Fr,
+    signature: &Signature<Projective>,
+) -> bool {
+    let Signature { sigma, theta } = signature;
+    let R0 = theta.Z0 - *vk * sigma.c0;
+    let R1 = Projective::generator() * sigma.z1 - theta.Z0 * sigma.c1;
+    sigma.c0 == challenge(config, &theta.m0, vk, &R0.into_affine())
+        && sigma.c1 == challenge(config, message, &theta.Z0.into_affine(), &R1.into_affine())
+}
+
+/// A Grumpkin scalar as 254 little-endian bits.
+#[derive(Clone)]
+pub struct ScalarVar(pub Vec<Boolean<Fq>>);
+
+impl ScalarVar {
+    /// The low and high 128 bits, as absorbed by [`challenge`].
+    pub fn limbs(&self) -> Result<[FpVar<Fq>; 2], SynthesisError> {
+        Ok([
+            Boolean::le_bits_to_fp(&self.0[..128])?,
+            Boolean::le_bits_to_fp(&self.0[128..])?,
+        ])
+    }
+}
+
+impl AllocVar<Fr, Fq> for ScalarVar {
+    /// Allocates the two limbs with `mode`, so that public scalars cost two public inputs, and
+    /// decomposes them into bits.
+    fn new_variable<T: Borrow<Fr>>(
+        cs: impl Into<Namespace<Fq>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let limbs = f().map(|scalar| split_scalar(*scalar.borrow()));
+        let lo = FpVar::new_variable(cs.clone(), || limbs.map(|[lo, _]| lo), mode)?;
+        let hi = FpVar::new_variable(cs, || limbs.map(|[_, hi]| hi), mode)?;
+        let (mut bits, _) = lo.to_bits_le_with_top_bits_zero(128)?;
+        bits.extend(hi.to_bits_le_with_top_bits_zero(126)?.0);
+        Ok(Self(bits))
+    }
+}
+
+/// A [`Signature`] in circuit.
+#[derive(Clone)]
+pub struct SignatureVar {
+    pub c0: ScalarVar,
+    pub c1: ScalarVar,
+    pub z1: ScalarVar,
+    pub m0: ScalarVar,
+    pub Z0: GVar,
+}
+
+impl AllocVar<Signature<Projective>, Fq> for SignatureVar {
+    fn new_variable<T: Borrow<Signature<Projective>>>(
+        cs: impl Into<Namespace<Fq>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let signature = f().map(|signature| signature.borrow().clone());
+        let scalar = |get: fn(&Signature<Projective>) -> Fr| {
+            ScalarVar::new_variable(
+                cs.clone(),
+                || signature.as_ref().map(get).map_err(|e| *e),
+                mode,
+            )
+        };
+        Ok(Self {
+            c0: scalar(|s| s.sigma.c0)?,
+            c1: scalar(|s| s.sigma.c1)?,
+            z1: scalar(|s| s.sigma.z1)?,
+            m0: scalar(|s| s.theta.m0)?,
+            Z0: GVar::new_variable(
+                cs.clone(),
+                || signature.as_ref().map(|s| s.theta.Z0).map_err(|e| *e),
+                mode,
+            )?,
+        })
+    }
+}
+
+/// Enforces `H(f, p, q) == c`.
+fn enforce_challenge(
+    cs: ConstraintSystemRef<Fq>,
+    config: &PoseidonConfig<Fq>,
+    c: &ScalarVar,
+    f: &ScalarVar,
+    p: &GVar,
+    q: &GVar,
+) -> Result<(), SynthesisError> {
+    let mut inputs = f.limbs()?.to_vec();
+    for point in [p, q] {
+        let point = point.to_affine()?;
+        inputs.extend([point.x, point.y]);
+    }
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&inputs)?;
+    let challenge = sponge.squeeze_field_elements(1)?.remove(0);
+    // Both sides are canonical: `to_bits_le` enforces it for the challenge, and the challenge is
+    // smaller than 2^254
+    challenge.to_bits_le()?[..254].enforce_equal(&c.0)
+}
+
+/// Enforces both challenge equations of AN23 verification, as [`verify`] checks them natively.
+pub fn verify_gadget(
+    cs: ConstraintSystemRef<Fq>,
+    config: &PoseidonConfig<Fq>,
+    vk: &GVar,
+    message: &ScalarVar,
+    signature: &SignatureVar,
+) -> Result<(), SynthesisError> {
+    let generator = GVar::constant(Projective::generator());
+
+    //       R0 = Z0 - [c0]X
+    let R0 = &signature.Z0 - vk.scalar_mul_le(signature.c0.0.iter())?;
+    enforce_challenge(cs.clone(), config, &signature.c0, &signature.m0, vk, &R0)?;
+
+    //       R1 = [z1]G - [c1]Z0
+    let R1 = generator.scalar_mul_le(signature.z1.0.iter())?
+        - signature.Z0.scalar_mul_le(signature.c1.0.iter())?;
+    enforce_challenge(cs, config, &signature.c1, message, &signature.Z0, &R1)
+}
+
+/// "`signature` is a valid signature on the public `message` under the public `vk`."
+#[derive(Clone)]
+pub struct VerificationCircuit {
+    pub config: PoseidonConfig<Fq>,
+    pub vk: Option<VerificationKey<Projective>>,
+    pub message: Option<Fr>,
+    pub signature: Option<Signature<Projective>>,
+}
+
+impl ConstraintSynthesizer<Fq> for VerificationCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> Result<(), SynthesisError> {
+        let vk = GVar::new_input(cs.clone(), || {
+            self.vk
+                .map(Projective::from)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let message = ScalarVar::new_input(cs.clone(), || {
+            self.message.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let signature = SignatureVar::new_witness(cs.clone(), || {
+            self.signature.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        verify_gadget(cs, &self.config, &vk, &message, &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec};
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+    use rand::Rng;
+
+    type Scheme = AN23ProxySignature<Projective, Poseidon>;
+
+    fn delegated_sign(rng: &mut impl Rng, message: &Fr) -> (Affine, Signature<Projective>) {
+        let parameters = Scheme::setup(rng).unwrap();
+        let (sk, vk) = Scheme::keygen(rng, &parameters).unwrap();
+        let (mut tokens, _) = Scheme::delegate(
+            rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let signature = Scheme::delegated_sign(rng, &parameters, &mut tokens, message).unwrap();
+        (vk, signature)
+    }
+
+    fn is_satisfied(circuit: VerificationCircuit) -> (bool, usize) {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        (cs.is_satisfied().unwrap(), cs.num_constraints())
+    }
+
+    #[test]
+    fn test_native_circuit_equivalence() {
+        let mut rng = test_rng();
+        let config = poseidon_config();
+        let message = Fr::rand(&mut rng);
+        let (vk, signature) = delegated_sign(&mut rng, &message);
+
+        let mut tampered_c0 = signature.clone();
+        tampered_c0.sigma.c0 += Fr::from(1u64);
+        let mut tampered_z1 = signature.clone();
+        tampered_z1.sigma.z1 += Fr::from(1u64);
+        let mut tampered_m0 = signature.clone();
+        tampered_m0.theta.m0 += Fr::from(1u64);
+        let other_vk = (Projective::generator() * Fr::rand(&mut rng)).into_affine();
+
+        for (i, (vk, message, signature)) in [
+            (vk, message, signature.clone()),
+            (vk, message + Fr::from(1u64), signature.clone()),
+            (other_vk, message, signature.clone()),
+            (vk, message, tampered_c0),
+            (vk, message, tampered_z1),
+            (vk, message, tampered_m0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let native = verify(&config, &vk, &message, &signature);
+            let (satisfied, _) = is_satisfied(VerificationCircuit {
+                config: config.clone(),
+                vk: Some(vk),
+                message: Some(message),
+                signature: Some(signature),
+            });
+            assert_eq!(native, i == 0);
+            assert_eq!(satisfied, native);
+        }
+    }
+
+    #[test]
+    fn test_constraint_count() {
+        let mut rng = test_rng();
+        let config = poseidon_config();
+        let message = Fr::rand(&mut rng);
+        let (vk, signature) = delegated_sign(&mut rng, &message);
+
+        let (satisfied, num_constraints) = is_satisfied(VerificationCircuit {
+            config,
+            vk: Some(vk),
+            message: Some(message),
+            signature: Some(signature),
+        });
+        assert!(satisfied);
+        // Dominated by the four 254-bit variable-base scalar multiplications
+        assert_eq!(num_constraints, 10509);
+    }
+}
@@ -0,0 +1,226 @@
+//! Persistent, crash-safe revocation store backed by [redb](https://docs.rs/redb).
+//!
+//! Every insert is a durable write transaction. redb serializes write transactions, so two
+//! verifiers sharing a [`RevocationDb`] cannot both accept the same token.
+
+use crate::Error;
+use crate::revocation_store::RevocationStore;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use redb::{Database, ReadableTable, ReadableTableMetadata, TableDefinition};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Compressed `m0` -> nothing.
+const REVOKED: TableDefinition<&[u8], ()> = TableDefinition::new("revoked");
+
+pub struct RevocationDb<F> {
+    db: Database,
+    _marker: PhantomData<F>,
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> RevocationDb<F> {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let db = Database::create(path).map_err(|_| Error::StorageFailed)?;
+
+        // Create the table up front so that read transactions never miss it
+        let txn = db.begin_write().map_err(|_| Error::StorageFailed)?;
+        txn.open_table(REVOKED).map_err(|_| Error::StorageFailed)?;
+        txn.commit().map_err(|_| Error::StorageFailed)?;
+
+        Ok(Self {
+            db,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn contains(&self, m0: &F) -> Result<bool, Error> {
+        let key = Self::key(m0)?;
+        let txn = self.db.begin_read().map_err(|_| Error::StorageFailed)?;
+        let table = txn.open_table(REVOKED).map_err(|_| Error::StorageFailed)?;
+        let entry = table
+            .get(key.as_slice())
+            .map_err(|_| Error::StorageFailed)?;
+        Ok(entry.is_some())
+    }
+
+    /// Durably inserts `m0` unless it is already present. Returns whether it was inserted.
+    pub fn insert_if_absent(&self, m0: &F) -> Result<bool, Error> {
+        self.insert_all([m0]).map(|inserted| inserted == 1)
+    }
+
+    /// Durably inserts all entries in a single transaction. Returns the number of new entries.
+    pub fn insert_all<'a>(&self, entries: impl IntoIterator<Item = &'a F>) -> Result<usize, Error>
+    where
+        F: 'a,
+    {
+        let txn = self.db.begin_write().map_err(|_| Error::StorageFailed)?;
+        let mut inserted = 0;
+        {
+            let mut table = txn.open_table(REVOKED).map_err(|_| Error::StorageFailed)?;
+            for m0 in entries {
+                let key = Self::key(m0)?;
+                let previous = table
+                    .insert(key.as_slice(), ())
+                    .map_err(|_| Error::StorageFailed)?;
+                if previous.is_none() {
+                    inserted += 1;
+                }
+            }
+        }
+        txn.commit().map_err(|_| Error::StorageFailed)?;
+        Ok(inserted)
+    }
+
+    pub fn len(&self) -> Result<u64, Error> {
+        let txn = self.db.begin_read().map_err(|_| Error::StorageFailed)?;
+        let table = txn.open_table(REVOKED).map_err(|_| Error::StorageFailed)?;
+        table.len().map_err(|_| Error::StorageFailed)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Exports all entries, in key order. The result can be used as an in-memory revocation state.
+    pub fn export(&self) -> Result<Vec<F>, Error> {
+        let txn = self.db.begin_read().map_err(|_| Error::StorageFailed)?;
+        let table = txn.open_table(REVOKED).map_err(|_| Error::StorageFailed)?;
+        table
+            .iter()
+            .map_err(|_| Error::StorageFailed)?
+            .map(|entry| {
+                let (key, _) = entry.map_err(|_| Error::StorageFailed)?;
+                F::deserialize_compressed(key.value()).map_err(|_| Error::SerializationFailed)
+            })
+            .collect()
+    }
+
+    /// Imports an in-memory revocation state, e.g. one saved with `CanonicalSerialize` before the
+    /// verifier moved to a persistent store. Returns the number of new entries.
+    pub fn migrate_from(&self, rev_state: &[F]) -> Result<usize, Error> {
+        self.insert_all(rev_state)
+    }
+
+    /// Reclaims unused space in the database file. Returns whether anything was compacted.
+    pub fn compact(&mut self) -> Result<bool, Error> {
+        self.db.compact().map_err(|_| Error::StorageFailed)
+    }
+
+    fn key(m0: &F) -> Result<Vec<u8>, Error> {
+        let mut key = Vec::new();
+        m0.serialize_compressed(&mut key)
+            .map_err(|_| Error::SerializationFailed)?;
+        Ok(key)
+    }
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> RevocationStore<F> for RevocationDb<F> {
+    fn contains(&self, m0: &F) -> Result<bool, Error> {
+        RevocationDb::contains(self, m0)
+    }
+
+    fn insert_if_absent(&mut self, m0: &F) -> Result<bool, Error> {
+        RevocationDb::insert_if_absent(self, m0)
+    }
+}
+
+/// Lets several verifiers share one database, e.g. across threads.
+impl<F: CanonicalSerialize + CanonicalDeserialize> RevocationStore<F> for &RevocationDb<F> {
+    fn contains(&self, m0: &F) -> Result<bool, Error> {
+        RevocationDb::contains(self, m0)
+    }
+
+    fn insert_if_absent(&mut self, m0: &F) -> Result<bool, Error> {
+        RevocationDb::insert_if_absent(self, m0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec};
+    use ark_ff::UniformRand;
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_revocation_db_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("revocation.redb");
+        let mut rng = test_rng();
+        let entries = (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+        {
+            let db = RevocationDb::<Fr>::open(&path).unwrap();
+            assert!(db.insert_if_absent(&entries[0]).unwrap());
+            assert!(!db.insert_if_absent(&entries[0]).unwrap());
+            assert_eq!(db.migrate_from(&entries).unwrap(), 2);
+        }
+
+        let mut db = RevocationDb::<Fr>::open(&path).unwrap();
+        assert_eq!(db.len().unwrap(), 3);
+        assert!(entries.iter().all(|m0| db.contains(m0).unwrap()));
+        assert!(!db.contains(&Fr::rand(&mut rng)).unwrap());
+
+        db.compact().unwrap();
+        let mut exported = db.export().unwrap();
+        let mut expected = entries.clone();
+        exported.sort();
+        expected.sort();
+        assert_eq!(exported, expected);
+    }
+
+    #[test]
+    fn test_concurrent_verifiers_accept_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = RevocationDb::<Fr>::open(dir.path().join("revocation.redb")).unwrap();
+
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+        let (mut delegation_info, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let message = Fr::rand(&mut rng);
+        let signature = AN23ProxySignature::<Projective>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut delegation_info,
+            &message,
+        )
+        .unwrap();
+
+        let accepted = std::thread::scope(|scope| {
+            let handles = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        AN23ProxySignature::<Projective>::verify_with_store(
+                            &parameters,
+                            &vk,
+                            &message,
+                            &signature,
+                            &mut &db,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .filter(|decision| *decision == Ok(true))
+                .count()
+        });
+
+        assert_eq!(accepted, 1);
+        assert_eq!(db.len().unwrap(), 1);
+    }
+}
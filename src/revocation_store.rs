@@ -0,0 +1,25 @@
+use crate::Error;
+
+/// Storage for the spent and revoked token identifiers `m0` checked by verifiers.
+pub trait RevocationStore<F> {
+    /// Whether `m0` has been spent or revoked.
+    fn contains(&self, m0: &F) -> Result<bool, Error>;
+
+    /// Atomically inserts `m0` unless it is already present. Returns whether it was inserted.
+    fn insert_if_absent(&mut self, m0: &F) -> Result<bool, Error>;
+}
+
+/// In-memory revocation state, as used by [`crate::ProxySignature::verify`].
+impl<F: PartialEq + Clone> RevocationStore<F> for Vec<F> {
+    fn contains(&self, m0: &F) -> Result<bool, Error> {
+        Ok(self.as_slice().contains(m0))
+    }
+
+    fn insert_if_absent(&mut self, m0: &F) -> Result<bool, Error> {
+        if self.as_slice().contains(m0) {
+            return Ok(false);
+        }
+        self.push(m0.clone());
+        Ok(true)
+    }
+}
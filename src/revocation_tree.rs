@@ -0,0 +1,419 @@
+//! A Merkle tree of revoked token identifiers `m0` with proofs of non-membership, so that a
+//! circuit can check that a token is not revoked against a public root.
+//!
+//! The tree is an indexed Merkle tree: leaves are appended in insertion order, and each one holds
+//! a revoked identifier together with the next larger one, so that the leaves form a sorted
+//! linked list from the sentinel `0` up to the sentinel `-1`. An identifier is absent if it lies
+//! strictly between a leaf and its successor. Inserting updates two leaves, so it costs
+//! `O(depth)` hashes, and only the nodes left of the last leaf are stored. Leaves are the
+//! Poseidon hash of the low and high 128 bits of the identifier and of its successor, empty
+//! leaves are `0` and nodes hash their two children, all with
+//! [`poseidon_config`](crate::r1cs::poseidon_config).
+
+use crate::Error;
+use crate::aztec::split_scalar;
+use crate::r1cs::{ScalarVar, shared_poseidon_config};
+use crate::revocation_store::RevocationStore;
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::{One, Zero};
+use ark_grumpkin::{Fq, Fr};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// The largest supported depth, with room for `2^32 - 1` revoked identifiers.
+pub const MAX_DEPTH: usize = 32;
+
+/// An indexed Merkle tree of revoked identifiers.
+#[derive(Clone, Debug)]
+pub struct RevocationTree {
+    depth: usize,
+    /// The leaf index of the sentinel `0` and of each revoked identifier.
+    indices: BTreeMap<Fr, usize>,
+    /// `levels[0]` are the leaves and `levels[depth]` the root, up to the last non-empty node.
+    levels: Vec<Vec<Fq>>,
+    /// The root of an empty subtree of each height.
+    empty: Vec<Fq>,
+}
+
+/// Proof that an identifier lies strictly between the leaf `index`, holding `low`, and its
+/// successor `high`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonMembershipProof {
+    pub index: u64,
+    pub low: Fr,
+    pub high: Fr,
+    /// Siblings from the leaf up.
+    pub path: Vec<Fq>,
+}
+
+impl RevocationTree {
+    /// An empty tree with room for `2^depth - 1` revoked identifiers. Fails with
+    /// [`Error::SetupFailed`] above [`MAX_DEPTH`].
+    pub fn new(depth: usize) -> Result<Self, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::SetupFailed);
+        }
+        let config = shared_poseidon_config();
+        let mut empty = vec![Fq::zero()];
+        for height in 0..depth {
+            empty.push(node_hash(config, &empty[height], &empty[height]));
+        }
+        let mut tree = Self {
+            depth,
+            indices: BTreeMap::from([(Fr::zero(), 0)]),
+            levels: vec![Vec::new(); depth + 1],
+            empty,
+        };
+        tree.set_leaf(0, leaf_hash(config, &Fr::zero(), &-Fr::one()));
+        Ok(tree)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn root(&self) -> Fq {
+        self.levels[self.depth][0]
+    }
+
+    /// The number of revoked identifiers.
+    pub fn len(&self) -> usize {
+        self.indices.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Proves that `m0` is not revoked. Fails with [`Error::UseOfRevokedToken`] if it is, and
+    /// with [`Error::InvalidToken`] for the sentinels.
+    pub fn non_membership_proof(&self, m0: &Fr) -> Result<NonMembershipProof, Error> {
+        if m0.is_zero() || *m0 == -Fr::one() {
+            return Err(Error::InvalidToken);
+        }
+        if self.indices.contains_key(m0) {
+            return Err(Error::UseOfRevokedToken);
+        }
+        let (low, index) = self.predecessor(m0);
+        Ok(NonMembershipProof {
+            index: index as u64,
+            low,
+            high: self.successor(m0),
+            path: self.path(index),
+        })
+    }
+
+    /// The largest leaf below `m0`, which exists since `0` is a leaf.
+    fn predecessor(&self, m0: &Fr) -> (Fr, usize) {
+        let (key, index) = self.indices.range(..*m0).next_back().unwrap();
+        (*key, *index)
+    }
+
+    /// The smallest leaf above `m0`, or the sentinel `-1`.
+    fn successor(&self, m0: &Fr) -> Fr {
+        self.indices
+            .range(*m0..)
+            .find(|(key, _)| *key != m0)
+            .map_or(-Fr::one(), |(key, _)| *key)
+    }
+
+    fn node(&self, height: usize, index: usize) -> Fq {
+        self.levels[height]
+            .get(index)
+            .copied()
+            .unwrap_or(self.empty[height])
+    }
+
+    fn path(&self, index: usize) -> Vec<Fq> {
+        (0..self.depth)
+            .map(|height| self.node(height, (index >> height) ^ 1))
+            .collect()
+    }
+
+    /// Sets a leaf and rehashes its path. Leaves are set at most one past the last one, so each
+    /// level stays contiguous.
+    fn set_leaf(&mut self, mut index: usize, leaf: Fq) {
+        let config = shared_poseidon_config();
+        let mut node = leaf;
+        for height in 0..=self.depth {
+            let nodes = &mut self.levels[height];
+            if index == nodes.len() {
+                nodes.push(node);
+            } else {
+                nodes[index] = node;
+            }
+            if height < self.depth {
+                let sibling = self.node(height, index ^ 1);
+                node = if index & 1 == 0 {
+                    node_hash(config, &node, &sibling)
+                } else {
+                    node_hash(config, &sibling, &node)
+                };
+                index >>= 1;
+            }
+        }
+    }
+}
+
+impl RevocationStore<Fr> for RevocationTree {
+    fn contains(&self, m0: &Fr) -> Result<bool, Error> {
+        Ok(self.indices.contains_key(m0) || *m0 == -Fr::one())
+    }
+
+    /// Fails with [`Error::StorageFailed`] if the tree is full.
+    fn insert_if_absent(&mut self, m0: &Fr) -> Result<bool, Error> {
+        if self.contains(m0)? {
+            return Ok(false);
+        }
+        let index = self.indices.len();
+        // In u64, since `usize` is 32 bits on wasm32 and `depth` can be 32
+        if index as u64 == 1u64 << self.depth {
+            return Err(Error::StorageFailed);
+        }
+        let config = shared_poseidon_config();
+        let (low, low_index) = self.predecessor(m0);
+        let high = self.successor(m0);
+        self.set_leaf(low_index, leaf_hash(config, &low, m0));
+        self.set_leaf(index, leaf_hash(config, m0, &high));
+        self.indices.insert(*m0, index);
+        Ok(true)
+    }
+}
+
+impl NonMembershipProof {
+    /// Checks the proof natively, the same statement as [`enforce_non_membership`].
+    pub fn verify(&self, root: &Fq, m0: &Fr) -> bool {
+        let config = shared_poseidon_config();
+        self.low < *m0
+            && *m0 < self.high
+            && self.path.len() <= MAX_DEPTH
+            && self.index < 1 << self.path.len()
+            && path_root(
+                config,
+                leaf_hash(config, &self.low, &self.high),
+                self.index as usize,
+                &self.path,
+            ) == *root
+    }
+}
+
+/// A [`NonMembershipProof`] in circuit.
+pub struct NonMembershipProofVar {
+    pub index: FpVar<Fq>,
+    pub low: ScalarVar,
+    pub high: ScalarVar,
+    pub path: Vec<FpVar<Fq>>,
+}
+
+impl NonMembershipProofVar {
+    /// Allocates a proof for a tree of `depth` as witnesses.
+    pub fn new_witness(
+        cs: ConstraintSystemRef<Fq>,
+        proof: Option<&NonMembershipProof>,
+        depth: usize,
+    ) -> Result<Self, SynthesisError> {
+        let missing = || SynthesisError::AssignmentMissing;
+        Ok(Self {
+            index: FpVar::new_witness(cs.clone(), || {
+                proof.map(|proof| Fq::from(proof.index)).ok_or_else(missing)
+            })?,
+            low: ScalarVar::new_witness(cs.clone(), || {
+                proof.map(|proof| proof.low).ok_or_else(missing)
+            })?,
+            high: ScalarVar::new_witness(cs.clone(), || {
+                proof.map(|proof| proof.high).ok_or_else(missing)
+            })?,
+            path: (0..depth)
+                .map(|i| {
+                    FpVar::new_witness(cs.clone(), || {
+                        proof.map(|proof| proof.path[i]).ok_or_else(missing)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// Enforces that `m0` is not a leaf of the tree with root `root`.
+pub fn enforce_non_membership(
+    cs: ConstraintSystemRef<Fq>,
+    config: &PoseidonConfig<Fq>,
+    root: &FpVar<Fq>,
+    m0: &ScalarVar,
+    proof: &NonMembershipProofVar,
+) -> Result<(), SynthesisError> {
+    // The index fits in `depth` bits
+    let (index, _) = proof
+        .index
+        .to_bits_le_with_top_bits_zero(proof.path.len())?;
+
+    is_less(&proof.low, m0)?.enforce_equal(&Boolean::TRUE)?;
+    is_less(m0, &proof.high)?.enforce_equal(&Boolean::TRUE)?;
+
+    let [low_lo, low_hi] = proof.low.limbs()?;
+    let [high_lo, high_hi] = proof.high.limbs()?;
+    let mut node = hash_var(cs.clone(), config, &[low_lo, low_hi, high_lo, high_hi])?;
+    for (is_right, sibling) in index.iter().zip(&proof.path) {
+        let left = is_right.select(sibling, &node)?;
+        let right = is_right.select(&node, sibling)?;
+        node = hash_var(cs.clone(), config, &[left, right])?;
+    }
+    node.enforce_equal(root)
+}
+
+/// `a < b` for scalars, comparing the high limbs and then the low limbs.
+fn is_less(a: &ScalarVar, b: &ScalarVar) -> Result<Boolean<Fq>, SynthesisError> {
+    // The limbs are at most 128 bits, so the comparisons are sound
+    let [a_lo, a_hi] = a.limbs()?;
+    let [b_lo, b_hi] = b.limbs()?;
+    let hi_less = a_hi.is_cmp(&b_hi, Ordering::Less, false)?;
+    let hi_equal = a_hi.is_eq(&b_hi)?;
+    let lo_less = a_lo.is_cmp(&b_lo, Ordering::Less, false)?;
+    Ok(hi_less | (hi_equal & lo_less))
+}
+
+fn hash_var(
+    cs: ConstraintSystemRef<Fq>,
+    config: &PoseidonConfig<Fq>,
+    inputs: &[FpVar<Fq>],
+) -> Result<FpVar<Fq>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&inputs.to_vec())?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+fn hash(config: &PoseidonConfig<Fq>, inputs: &[Fq]) -> Fq {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&inputs.to_vec());
+    sponge.squeeze_native_field_elements(1)[0]
+}
+
+fn leaf_hash(config: &PoseidonConfig<Fq>, key: &Fr, next: &Fr) -> Fq {
+    let [key_lo, key_hi] = split_scalar(*key);
+    let [next_lo, next_hi] = split_scalar(*next);
+    hash(config, &[key_lo, key_hi, next_lo, next_hi])
+}
+
+fn node_hash(config: &PoseidonConfig<Fq>, left: &Fq, right: &Fq) -> Fq {
+    hash(config, &[*left, *right])
+}
+
+fn path_root(config: &PoseidonConfig<Fq>, leaf: Fq, mut index: usize, path: &[Fq]) -> Fq {
+    path.iter().fold(leaf, |node, sibling| {
+        let node = if index & 1 == 0 {
+            node_hash(config, &node, sibling)
+        } else {
+            node_hash(config, sibling, &node)
+        };
+        index >>= 1;
+        node
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    fn is_satisfied(root: Fq, m0: Fr, proof: &NonMembershipProof, depth: usize) -> bool {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let root = FpVar::new_input(cs.clone(), || Ok(root)).unwrap();
+        let m0 = ScalarVar::new_witness(cs.clone(), || Ok(m0)).unwrap();
+        let proof = NonMembershipProofVar::new_witness(cs.clone(), Some(proof), depth).unwrap();
+        enforce_non_membership(cs.clone(), shared_poseidon_config(), &root, &m0, &proof).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_non_membership() {
+        let mut rng = test_rng();
+        let mut tree = RevocationTree::new(3).unwrap();
+        let revoked = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        for m0 in &revoked {
+            assert!(tree.insert_if_absent(m0).unwrap());
+        }
+        assert!(!tree.insert_if_absent(&revoked[0]).unwrap());
+        assert_eq!(tree.len(), 5);
+        assert_eq!(
+            tree.non_membership_proof(&revoked[1]),
+            Err(Error::UseOfRevokedToken)
+        );
+        assert_eq!(
+            tree.non_membership_proof(&-Fr::one()),
+            Err(Error::InvalidToken)
+        );
+
+        let m0 = Fr::rand(&mut rng);
+        let proof = tree.non_membership_proof(&m0).unwrap();
+        assert!(proof.verify(&tree.root(), &m0));
+        assert!(is_satisfied(tree.root(), m0, &proof, 3));
+
+        // A proof against an older root does not carry over once m0 is revoked
+        tree.insert_if_absent(&m0).unwrap();
+        assert!(!proof.verify(&tree.root(), &m0));
+        assert!(!is_satisfied(tree.root(), m0, &proof, 3));
+
+        // A leaf cannot claim a successor it does not link to
+        let mut gap = tree.non_membership_proof(&(m0 - Fr::one())).unwrap();
+        assert_eq!(gap.high, m0);
+        gap.high = tree.successor(&m0);
+        assert!(!gap.verify(&tree.root(), &m0));
+        assert!(!is_satisfied(tree.root(), m0, &gap, 3));
+
+        // The tree holds 2^3 - 1 identifiers
+        tree.insert_if_absent(&Fr::rand(&mut rng)).unwrap();
+        assert_eq!(tree.len(), 7);
+        assert_eq!(
+            tree.insert_if_absent(&Fr::rand(&mut rng)),
+            Err(Error::StorageFailed)
+        );
+    }
+
+    #[test]
+    fn test_tree_depth() {
+        assert_eq!(
+            RevocationTree::new(MAX_DEPTH + 1).unwrap_err(),
+            Error::SetupFailed
+        );
+
+        // Deep trees only store their non-empty nodes
+        let mut rng = test_rng();
+        let mut tree = RevocationTree::new(MAX_DEPTH).unwrap();
+        for _ in 0..100 {
+            tree.insert_if_absent(&Fr::rand(&mut rng)).unwrap();
+        }
+        assert_eq!(tree.levels[0].len(), 101);
+        assert_eq!(tree.levels[MAX_DEPTH].len(), 1);
+
+        let m0 = Fr::rand(&mut rng);
+        let proof = tree.non_membership_proof(&m0).unwrap();
+        assert_eq!(proof.path.len(), MAX_DEPTH);
+        assert!(proof.verify(&tree.root(), &m0));
+
+        // Incremental updates agree with hashing all the leaves
+        let mut tree = RevocationTree::new(4).unwrap();
+        for _ in 0..10 {
+            tree.insert_if_absent(&Fr::rand(&mut rng)).unwrap();
+        }
+        let config = shared_poseidon_config();
+        let mut leaves = vec![Fq::zero(); 16];
+        for (key, index) in &tree.indices {
+            leaves[*index] = leaf_hash(config, key, &tree.successor(key));
+        }
+        while leaves.len() > 1 {
+            leaves = leaves
+                .chunks_exact(2)
+                .map(|children| node_hash(config, &children[0], &children[1]))
+                .collect();
+        }
+        assert_eq!(leaves[0], tree.root());
+    }
+}
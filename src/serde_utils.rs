@@ -0,0 +1,186 @@
+//! Serde support for arkworks types, used with `#[serde(with = "...")]`.
+//!
+//! Human-readable formats get the hex of the compressed canonical encoding, as printed by the
+//! CLI; binary formats get the raw bytes. Deserialization validates the encoding, so points are
+//! checked to be on the curve and in the prime-order subgroup, and field elements to be canonical.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serializer};
+use std::fmt;
+
+pub(crate) mod canonical {
+    use super::*;
+
+    pub fn serialize<T: CanonicalSerialize, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut bytes = Vec::new();
+        value
+            .serialize_compressed(&mut bytes)
+            .map_err(serde::ser::Error::custom)?;
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(bytes))
+        } else {
+            serializer.serialize_bytes(&bytes)
+        }
+    }
+
+    pub fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let bytes = if deserializer.is_human_readable() {
+            let hex = String::deserialize(deserializer)?;
+            hex::decode(hex.strip_prefix("0x").unwrap_or(&hex)).map_err(de::Error::custom)?
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor)?
+        };
+        T::deserialize_compressed(&*bytes).map_err(de::Error::custom)
+    }
+}
+
+pub(crate) mod canonical_option {
+    use super::*;
+
+    pub fn serialize<T: CanonicalSerialize, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(&Wrapper(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error> {
+        Ok(Option::<Unwrapper<T>>::deserialize(deserializer)?.map(|value| value.0))
+    }
+
+    struct Wrapper<'a, T>(&'a T);
+
+    impl<T: CanonicalSerialize> serde::Serialize for Wrapper<'_, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            canonical::serialize(self.0, serializer)
+        }
+    }
+
+    struct Unwrapper<T>(T);
+
+    impl<'de, T: CanonicalDeserialize> Deserialize<'de> for Unwrapper<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            canonical::deserialize(deserializer).map(Unwrapper)
+        }
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a compressed canonical encoding")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(bytes)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{
+        AN23ProxySignature, DelegationSpec, Parameters, Signature, SigningKey, SigningToken,
+    };
+    use ark_ff::{BigInteger, PrimeField, UniformRand};
+    use ark_grumpkin::{Affine, Fq, Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_serde_json_round_trip() {
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+        let (tokens, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 2,
+                bind_message_nonce: true,
+            },
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&parameters).unwrap();
+        let parameters: Parameters<Projective> = serde_json::from_str(&json).unwrap();
+        let json = serde_json::to_string(&sk).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SigningKey<Projective>>(&json)
+                .unwrap()
+                .0,
+            sk.0
+        );
+
+        let json = serde_json::to_value(&tokens).unwrap();
+        assert!(json[0]["r1"].is_string());
+        let mut tokens_from_json: Vec<SigningToken<Projective>> =
+            serde_json::from_value(json).unwrap();
+        assert_eq!(tokens_from_json.len(), 2);
+
+        let message = Fr::rand(&mut rng);
+        let signature = AN23ProxySignature::<Projective>::delegated_sign(
+            &mut rng,
+            &parameters,
+            &mut tokens_from_json,
+            &message,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&signature).unwrap();
+        let signature: Signature<Projective> = serde_json::from_str(&json).unwrap();
+        assert!(
+            AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut Vec::new(),
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serde_rejects_invalid_encodings() {
+        // The modulus of the scalar field is not a canonical field element
+        let modulus = hex::encode(Fr::MODULUS.to_bytes_le());
+        assert!(serde_json::from_str::<SigningKey<Projective>>(&format!("\"{modulus}\"")).is_err());
+
+        // An x-coordinate without a point on Grumpkin
+        let x = (0u64..)
+            .map(Fq::from)
+            .find(|x| Affine::get_point_from_x_unchecked(*x, false).is_none())
+            .unwrap();
+        let json = format!(
+            "{{\"generator\":\"{}\"}}",
+            hex::encode(x.into_bigint().to_bytes_le())
+        );
+        assert!(serde_json::from_str::<Parameters<Projective>>(&json).is_err());
+    }
+}
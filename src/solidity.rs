@@ -0,0 +1,690 @@
+//! An EVM verification path next to the Noir one: Solidity verifier contracts for AN23
+//! signatures, and the calldata of their `verify`.
+//!
+//! The contracts recompute the challenges with the SHA-256 precompile, so signatures must use
+//! [`Sha256`] challenges, see [`Scheme`], and the standard generator of [`ProxySignature::setup`].
+//! Over BN254, the ecAdd and ecMul precompiles compute `R0` and `R1`. secp256k1 has no such
+//! precompiles, so the caller passes `R0` and `R1` as hints, and the contract checks
+//! `[c0]X = Z0 - R0` and `R1 = [z1]G - [c1]Z0` on addresses with `ecrecover`.
+//!
+//! The contracts are stateless: callers must record `signature.m0` to reject revoked and
+//! reused tokens, like the revocation state of [`ProxySignature::verify`].
+//!
+//! [`ProxySignature::setup`]: crate::ProxySignature::setup
+//! [`ProxySignature::verify`]: crate::ProxySignature::verify
+
+use crate::NamedCurve;
+use crate::an23_proxy_signature::{AN23ProxySignature, Signature, VerificationKey};
+use crate::challenge_hash::Sha256;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+
+/// The scheme with the challenges of the Solidity verifiers.
+pub type Scheme<G> = AN23ProxySignature<G, Sha256>;
+
+/// A curve with a Solidity verifier.
+pub trait EvmCurve: CurveGroup<BaseField: PrimeField> + NamedCurve {
+    /// The selector of the contract's `verify`.
+    const VERIFY_SELECTOR: [u8; 4];
+
+    /// The contract's `verify` and its helpers.
+    const VERIFY_SOLIDITY: &'static str;
+
+    /// The points passed to `verify` after the signature.
+    fn hints(vk: &VerificationKey<Self>, signature: &Signature<Self>) -> Vec<Self::Affine>;
+}
+
+#[cfg(feature = "secp256k1")]
+impl EvmCurve for crate::curves::secp256k1::Projective {
+    /// `verify(uint256[2],uint256,(uint256,uint256,uint256,uint256,uint256,uint256),uint256[2],uint256[2])`
+    const VERIFY_SELECTOR: [u8; 4] = [0x1b, 0x63, 0xe7, 0xd9];
+
+    const VERIFY_SOLIDITY: &'static str = SECP256K1_VERIFY;
+
+    /// `R0 = Z0 - [c0]X` and `R1 = [z1]G - [c1]Z0`.
+    fn hints(vk: &VerificationKey<Self>, signature: &Signature<Self>) -> Vec<Self::Affine> {
+        use ark_ec::PrimeGroup;
+
+        let Signature { sigma, theta } = signature;
+        let r0 = theta.Z0 - *vk * sigma.c0;
+        let r1 = Self::generator() * sigma.z1 - theta.Z0 * sigma.c1;
+        Self::normalize_batch(&[r0, r1])
+    }
+}
+
+#[cfg(feature = "bn254")]
+impl EvmCurve for crate::curves::Bn254 {
+    /// `verify(uint256[2],uint256,(uint256,uint256,uint256,uint256,uint256,uint256))`
+    const VERIFY_SELECTOR: [u8; 4] = [0x22, 0x88, 0x1d, 0xf3];
+
+    const VERIFY_SOLIDITY: &'static str = BN254_VERIFY;
+
+    fn hints(_vk: &VerificationKey<Self>, _signature: &Signature<Self>) -> Vec<Self::Affine> {
+        Vec::new()
+    }
+}
+
+/// A Solidity contract `name` with `verify(vk, message, signature, ...hints)`, which returns
+/// whether the signature is valid.
+pub fn verifier_contract<G: EvmCurve>(name: &str) -> String {
+    let (gx, gy) = G::generator().into_affine().xy().unwrap_or_default();
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title AN23 proxy signature verifier over {curve}, generated by schnorr-tokens.
+/// @notice Challenges are SHA-256, as in `challenge_hash::Sha256`. Verification is stateless:
+/// record `signature.m0` to reject revoked and reused tokens.
+contract {name} {{
+    struct Signature {{
+        uint256 c0;
+        uint256 c1;
+        uint256 z1;
+        uint256 m0;
+        uint256 Z0x;
+        uint256 Z0y;
+    }}
+
+    // The base field modulus
+    uint256 constant P = 0x{p};
+    // The scalar field modulus
+    uint256 constant N = 0x{n};
+    uint256 constant GX = 0x{gx};
+    uint256 constant GY = 0x{gy};
+{verify}{challenge}}}
+"#,
+        curve = G::NAME,
+        p = hex::encode(G::BaseField::MODULUS.to_bytes_be()),
+        n = hex::encode(G::ScalarField::MODULUS.to_bytes_be()),
+        gx = hex::encode(word(&gx)),
+        gy = hex::encode(word(&gy)),
+        verify = G::VERIFY_SOLIDITY,
+        challenge = CHALLENGE,
+    )
+}
+
+/// The ABI encoding of a signature, as the contracts' `Signature` struct:
+/// `(c0, c1, z1, m0, Z0.x, Z0.y)`.
+pub fn encode_signature<G: EvmCurve>(signature: &Signature<G>) -> Vec<u8> {
+    let Signature { sigma, theta } = signature;
+    let mut words = [sigma.c0, sigma.c1, sigma.z1, theta.m0]
+        .iter()
+        .flat_map(word)
+        .collect::<Vec<_>>();
+    words.extend(encode_point::<G>(&theta.Z0.into_affine()));
+    words
+}
+
+/// The calldata of `verify` on a contract of [`verifier_contract`], hints included.
+pub fn verify_calldata<G: EvmCurve>(
+    vk: &VerificationKey<G>,
+    message: &G::ScalarField,
+    signature: &Signature<G>,
+) -> Vec<u8> {
+    let mut calldata = G::VERIFY_SELECTOR.to_vec();
+    calldata.extend(encode_point::<G>(vk));
+    calldata.extend(word(message));
+    calldata.extend(encode_signature(signature));
+    for hint in G::hints(vk, signature) {
+        calldata.extend(encode_point::<G>(&hint));
+    }
+    calldata
+}
+
+/// `uint256[2]` coordinates, with `(0, 0)` for the point at infinity like the precompiles.
+fn encode_point<G: CurveGroup<BaseField: PrimeField>>(point: &G::Affine) -> Vec<u8> {
+    let (x, y) = point.xy().unwrap_or_default();
+    [word(&x), word(&y)].concat()
+}
+
+/// A big-endian `uint256`.
+fn word<F: PrimeField>(value: &F) -> Vec<u8> {
+    let mut bytes = value.into_bigint().to_bytes_be();
+    let mut word = vec![0u8; 32usize.saturating_sub(bytes.len())];
+    word.append(&mut bytes);
+    word
+}
+
+#[cfg(feature = "secp256k1")]
+const SECP256K1_VERIFY: &str = r#"
+    /// Whether `signature` signs `message` under `vk`, given `r0 = Z0 - [c0]vk` and
+    /// `r1 = [z1]G - [c1]Z0`.
+    function verify(
+        uint256[2] calldata vk,
+        uint256 message,
+        Signature calldata signature,
+        uint256[2] calldata r0,
+        uint256[2] calldata r1
+    ) external view returns (bool) {
+        if (
+            message >= N || signature.c0 >= N || signature.c1 >= N || signature.z1 >= N
+                || signature.m0 >= N
+        ) {
+            return false;
+        }
+        uint256[2] memory z0 = [signature.Z0x, signature.Z0y];
+        if (!onCurve(vk) || !onCurve(z0) || !onCurve(r0) || !onCurve(r1)) {
+            return false;
+        }
+        // [c0]X = Z0 - R0
+        (bool ok, uint256[2] memory d) = subtract(z0, r0);
+        if (!ok || mulAddAddress(vk, signature.c0, 0) != pointAddress(d)) {
+            return false;
+        }
+        // R1 = [-c1]Z0 + [z1]G
+        if (mulAddAddress(z0, N - signature.c1, signature.z1) != pointAddress(r1)) {
+            return false;
+        }
+        return signature.c0 == challenge(signature.m0, vk, r0)
+            && signature.c1 == challenge(message, z0, r1);
+    }
+
+    /// The address of `[a]q + [b]G`. With `r = q.x` and `v` the parity of `q.y`,
+    /// `ecrecover(h, v, r, s)` is the address of `[s/r]q - [h/r]G`. Zero if `q.x >= N` or the
+    /// result is the point at infinity.
+    function mulAddAddress(uint256[2] memory q, uint256 a, uint256 b) internal view returns (address) {
+        if (q[0] >= N) {
+            return address(0);
+        }
+        uint256 s = mulmod(a, q[0], N);
+        uint256 h = (N - mulmod(b, q[0], N)) % N;
+        uint256[4] memory input = [h, 27 + (q[1] & 1), q[0], s];
+        uint256[1] memory signer;
+        assembly {
+            pop(staticcall(gas(), 0x01, input, 0x80, signer, 0x20))
+        }
+        return address(uint160(signer[0]));
+    }
+
+    /// The Ethereum address of a public key, never zero.
+    function pointAddress(uint256[2] memory p) internal pure returns (address) {
+        bytes32 digest;
+        assembly {
+            digest := keccak256(p, 0x40)
+        }
+        return address(uint160(uint256(digest)));
+    }
+
+    function onCurve(uint256[2] memory p) internal pure returns (bool) {
+        return p[0] < P && p[1] < P
+            && mulmod(p[1], p[1], P) == addmod(mulmod(mulmod(p[0], p[0], P), p[0], P), 7, P);
+    }
+
+    /// `p - q` for points on the curve, or `ok = false` if `p.x = q.x`.
+    function subtract(uint256[2] memory p, uint256[2] memory q)
+        internal
+        view
+        returns (bool ok, uint256[2] memory r)
+    {
+        if (p[0] == q[0]) {
+            return (false, r);
+        }
+        // The chord through p and -q
+        uint256 lambda = mulmod(addmod(p[1], q[1], P), inverse(addmod(p[0], P - q[0], P)), P);
+        r[0] = addmod(mulmod(lambda, lambda, P), P - addmod(p[0], q[0], P), P);
+        r[1] = addmod(mulmod(lambda, addmod(p[0], P - r[0], P), P), P - p[1], P);
+        return (true, r);
+    }
+
+    /// `x^(P - 2)` with the modexp precompile.
+    function inverse(uint256 x) internal view returns (uint256) {
+        uint256[6] memory input = [uint256(32), 32, 32, x, P - 2, P];
+        uint256[1] memory out;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x05, input, 0xc0, out, 0x20)
+        }
+        require(ok, "modexp failed");
+        return out[0];
+    }
+"#;
+
+#[cfg(feature = "bn254")]
+const BN254_VERIFY: &str = r#"
+    /// Whether `signature` signs `message` under `vk`.
+    function verify(uint256[2] calldata vk, uint256 message, Signature calldata signature)
+        external
+        view
+        returns (bool)
+    {
+        if (
+            message >= N || signature.c0 >= N || signature.c1 >= N || signature.z1 >= N
+                || signature.m0 >= N
+        ) {
+            return false;
+        }
+        uint256[2] memory z0 = [signature.Z0x, signature.Z0y];
+        uint256[2] memory zero;
+        // R0 = [-c0]X + Z0
+        (bool ok0, uint256[2] memory r0) = mulAdd(vk, N - signature.c0, z0);
+        // R1 = [-c1]Z0 + [z1]G
+        (bool ok1, uint256[2] memory z1g) = mulAdd([GX, GY], signature.z1, zero);
+        (bool ok2, uint256[2] memory r1) = mulAdd(z0, N - signature.c1, z1g);
+        return ok0 && ok1 && ok2 && signature.c0 == challenge(signature.m0, vk, r0)
+            && signature.c1 == challenge(message, z0, r1);
+    }
+
+    /// `[s]p + q` with the ecMul and ecAdd precompiles, or `ok = false` if a point is not on the
+    /// curve. `(0, 0)` is the point at infinity.
+    function mulAdd(uint256[2] memory p, uint256 s, uint256[2] memory q)
+        internal
+        view
+        returns (bool ok, uint256[2] memory r)
+    {
+        uint256[3] memory mulInput = [p[0], p[1], s];
+        assembly {
+            ok := staticcall(gas(), 0x07, mulInput, 0x60, r, 0x40)
+        }
+        if (!ok) {
+            return (false, r);
+        }
+        uint256[4] memory addInput = [r[0], r[1], q[0], q[1]];
+        assembly {
+            ok := staticcall(gas(), 0x06, addInput, 0x80, r, 0x40)
+        }
+    }
+"#;
+
+const CHALLENGE: &str = r#"
+    /// `challenge_hash::Sha256` of a scalar and two points: SHA-256 of their little-endian
+    /// encodings, with the last byte of the digest cleared, as a little-endian integer mod N.
+    function challenge(uint256 f, uint256[2] memory a, uint256[2] memory b)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256[5] memory input =
+            [reverseBytes(f), reverseBytes(a[0]), reverseBytes(a[1]), reverseBytes(b[0]), reverseBytes(b[1])];
+        uint256[1] memory digest;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x02, input, 0xa0, digest, 0x20)
+        }
+        require(ok, "sha256 failed");
+        return (reverseBytes(digest[0]) & type(uint248).max) % N;
+    }
+
+    /// Converts between big-endian words and little-endian encodings.
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v = ((v >> 8) & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v >> 16) & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v >> 32) & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v >> 64) & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::DelegationSpec;
+    use ark_ff::{One, UniformRand, Zero};
+    use ark_std::test_rng;
+    use revm::Evm;
+    use revm::db::InMemoryDB;
+    use revm::primitives::{AccountInfo, Address, Bytecode, TxKind, keccak256};
+
+    const ECRECOVER: u8 = 0x01;
+    const SHA256: u8 = 0x02;
+
+    /// A delegated signature on a random message.
+    fn delegated_signature<G: EvmCurve>() -> (VerificationKey<G>, G::ScalarField, Signature<G>) {
+        let mut rng = test_rng();
+        let parameters = Scheme::<G>::setup(&mut rng).unwrap();
+        let (sk, vk) = Scheme::<G>::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, _) = Scheme::<G>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let message = G::ScalarField::rand(&mut rng);
+        let signature =
+            Scheme::<G>::delegated_sign(&mut rng, &parameters, &mut tokens, &message).unwrap();
+        assert!(Scheme::<G>::verify(&parameters, &vk, &message, &signature, &mut vec![]).unwrap());
+        (vk, message, signature)
+    }
+
+    /// Calls `to` in the EVM, with `code` deployed there, and returns the output of a successful
+    /// call.
+    fn evm_call(to: Address, code: Option<Vec<u8>>, data: Vec<u8>) -> Option<Vec<u8>> {
+        let mut db = InMemoryDB::default();
+        if let Some(code) = code {
+            let code = Some(Bytecode::new_raw(code.into()));
+            db.insert_account_info(
+                to,
+                AccountInfo {
+                    code,
+                    ..AccountInfo::default()
+                },
+            );
+        }
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Call(to);
+                tx.data = data.into();
+            })
+            .build();
+        let result = evm.transact().unwrap().result;
+        result
+            .is_success()
+            .then(|| result.output().unwrap().to_vec())
+    }
+
+    fn precompile(address: u8, input: Vec<u8>) -> Vec<u8> {
+        evm_call(Address::with_last_byte(address), None, input).unwrap()
+    }
+
+    /// The arguments of `verify` in `calldata`, as words.
+    fn arguments(calldata: &[u8]) -> Vec<&[u8]> {
+        calldata[4..].chunks(32).collect()
+    }
+
+    /// The challenge of the contracts, with the SHA-256 precompile.
+    fn evm_challenge<G: EvmCurve>(words: &[&[u8]]) -> G::ScalarField {
+        let preimage = words
+            .iter()
+            .flat_map(|word| word.iter().rev().copied())
+            .collect();
+        let mut digest = precompile(SHA256, preimage);
+        digest[31] = 0;
+        G::ScalarField::from_le_bytes_mod_order(&digest)
+    }
+
+    #[test]
+    fn test_verify_selectors() {
+        let signature = "(uint256,uint256,uint256,uint256,uint256,uint256)";
+        #[cfg(feature = "secp256k1")]
+        assert_eq!(
+            keccak256(format!(
+                "verify(uint256[2],uint256,{signature},uint256[2],uint256[2])"
+            ))[..4],
+            crate::curves::secp256k1::Projective::VERIFY_SELECTOR
+        );
+        #[cfg(feature = "bn254")]
+        assert_eq!(
+            keccak256(format!("verify(uint256[2],uint256,{signature})"))[..4],
+            crate::curves::Bn254::VERIFY_SELECTOR
+        );
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_bn254_precompiles() {
+        use crate::curves::Bn254;
+        use ark_ec::PrimeGroup;
+        const EC_ADD: u8 = 0x06;
+        const EC_MUL: u8 = 0x07;
+
+        let (vk, message, signature) = delegated_signature::<Bn254>();
+        let calldata = verify_calldata(&vk, &message, &signature);
+        assert_eq!(calldata.len(), 4 + 9 * 32);
+        let [x, y, message_word, c0, c1, z1, m0, z0x, z0y] = arguments(&calldata)[..] else {
+            panic!("unexpected calldata length");
+        };
+        let negate = |scalar: &[u8]| {
+            word(&-<Bn254 as PrimeGroup>::ScalarField::from_be_bytes_mod_order(scalar))
+        };
+        let mul_add = |p: &[&[u8]], s: &[u8], q: &[u8]| {
+            let sp = precompile(EC_MUL, [p.concat(), s.to_vec()].concat());
+            precompile(EC_ADD, [sp, q.to_vec()].concat())
+        };
+
+        // R0 = [-c0]X + Z0 and R1 = [-c1]Z0 + [z1]G, as in `verify`
+        let r0 = mul_add(&[x, y], &negate(c0), &[z0x, z0y].concat());
+        let generator = encode_point::<Bn254>(&Bn254::generator().into_affine());
+        let z1g = mul_add(&[&generator[..32], &generator[32..]], z1, &[0; 64]);
+        let r1 = mul_add(&[z0x, z0y], &negate(c1), &z1g);
+
+        let challenge0 = evm_challenge::<Bn254>(&[m0, x, y, &r0[..32], &r0[32..]]);
+        let challenge1 = evm_challenge::<Bn254>(&[message_word, z0x, z0y, &r1[..32], &r1[32..]]);
+        assert_eq!(challenge0, signature.sigma.c0);
+        assert_eq!(challenge1, signature.sigma.c1);
+
+        let other = word(&(message + <Bn254 as PrimeGroup>::ScalarField::one()));
+        assert_ne!(
+            evm_challenge::<Bn254>(&[&other, z0x, z0y, &r1[..32], &r1[32..]]),
+            signature.sigma.c1
+        );
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn test_secp256k1_ecrecover() {
+        use crate::curves::secp256k1::{Fq, Fr, Projective};
+        use ark_ff::Field;
+
+        let (vk, message, signature) = delegated_signature::<Projective>();
+        let calldata = verify_calldata(&vk, &message, &signature);
+        assert_eq!(calldata.len(), 4 + 13 * 32);
+        let [
+            x,
+            y,
+            message_word,
+            c0,
+            c1,
+            z1,
+            m0,
+            z0x,
+            z0y,
+            r0x,
+            r0y,
+            r1x,
+            r1y,
+        ] = arguments(&calldata)[..]
+        else {
+            panic!("unexpected calldata length");
+        };
+        let scalar = Fr::from_be_bytes_mod_order;
+        let coordinate = Fq::from_be_bytes_mod_order;
+        // `mulAddAddress`: the address of [a]q + [b]G
+        let mul_add_address = |qx: &[u8], qy: &[u8], a: Fr, b: Fr| {
+            let r = scalar(qx);
+            let mut v = [0; 32];
+            v[31] = 27 + (qy[31] & 1);
+            let input = [word(&-(b * r)), v.to_vec(), qx.to_vec(), word(&(a * r))].concat();
+            precompile(ECRECOVER, input)[12..].to_vec()
+        };
+        let point_address = |px: &[u8], py: &[u8]| keccak256([px, py].concat())[12..].to_vec();
+
+        // `subtract`: Z0 - R0 along the chord through Z0 and -R0
+        let (px, py, qx, qy) = (
+            coordinate(z0x),
+            coordinate(z0y),
+            coordinate(r0x),
+            coordinate(r0y),
+        );
+        let lambda = (py + qy) * (px - qx).inverse().unwrap();
+        let dx = lambda.square() - (px + qx);
+        let dy = lambda * (px - dx) - py;
+        let d = (signature.theta.Z0
+            - Projective::from(ark_ec::short_weierstrass::Affine::new(qx, qy)))
+        .into_affine();
+        assert_eq!(d.xy(), Some((dx, dy)));
+
+        // [c0]X = Z0 - R0 and R1 = [-c1]Z0 + [z1]G
+        assert_eq!(
+            mul_add_address(x, y, scalar(c0), Fr::from(0u64)),
+            point_address(&word(&dx), &word(&dy))
+        );
+        assert_eq!(
+            mul_add_address(z0x, z0y, -scalar(c1), scalar(z1)),
+            point_address(r1x, r1y)
+        );
+        assert_ne!(
+            mul_add_address(z0x, z0y, -scalar(c1), scalar(z1) + Fr::one()),
+            point_address(r1x, r1y)
+        );
+
+        let challenge0 = evm_challenge::<Projective>(&[m0, x, y, r0x, r0y]);
+        let challenge1 = evm_challenge::<Projective>(&[message_word, z0x, z0y, r1x, r1y]);
+        assert_eq!(challenge0, signature.sigma.c0);
+        assert_eq!(challenge1, signature.sigma.c1);
+    }
+
+    /// The committed source and hex runtime bytecode of the verifier, see
+    /// `update_contract_fixtures`.
+    fn contract_fixture<G: EvmCurve>() -> (&'static str, &'static str) {
+        match G::NAME {
+            "secp256k1" => (
+                include_str!("../tests/fixtures/AN23Verifier_secp256k1.sol"),
+                include_str!("../tests/fixtures/AN23Verifier_secp256k1.bin-runtime"),
+            ),
+            "bn254" => (
+                include_str!("../tests/fixtures/AN23Verifier_bn254.sol"),
+                include_str!("../tests/fixtures/AN23Verifier_bn254.bin-runtime"),
+            ),
+            name => panic!("no contract fixture for {name}"),
+        }
+    }
+
+    fn check_contract<G: EvmCurve>() {
+        let (source, code) = contract_fixture::<G>();
+        assert!(
+            verifier_contract::<G>("AN23Verifier") == source,
+            "the {} verifier changed, regenerate its fixtures with `cargo test --features secp256k1,bn254 \
+             update_contract_fixtures -- --ignored`",
+            G::NAME
+        );
+        let code = hex::decode(code.trim()).unwrap();
+        let (vk, message, signature) = delegated_signature::<G>();
+        let call = |calldata: Vec<u8>| {
+            let output = evm_call(Address::with_last_byte(0xaa), Some(code.clone()), calldata);
+            output.unwrap() == word(&G::ScalarField::one())
+        };
+        let verify = |message: &G::ScalarField, signature: &Signature<G>| {
+            call(verify_calldata(&vk, message, signature))
+        };
+        assert!(verify(&message, &signature));
+        assert!(!verify(&(message + G::ScalarField::one()), &signature));
+        let mut tampered = signature.clone();
+        tampered.sigma.z1 += G::ScalarField::one();
+        assert!(!verify(&message, &tampered));
+
+        // Hints moved off the curve, by adding one to their y coordinates
+        let calldata = verify_calldata(&vk, &message, &signature);
+        for hint in 0..G::hints(&vk, &signature).len() {
+            let mut off_curve = calldata.clone();
+            let y = &mut off_curve[4 + (10 + 2 * hint) * 32..][..32];
+            let y_plus_one =
+                word(&(G::BaseField::from_be_bytes_mod_order(y) + G::BaseField::one()));
+            y.copy_from_slice(&y_plus_one);
+            assert!(!call(off_curve));
+        }
+
+        // Hostile calldata must make `verify` return false rather than revert
+        let rejects = |calldata: Vec<u8>| {
+            let output = evm_call(Address::with_last_byte(0xaa), Some(code.clone()), calldata);
+            output.unwrap() == word(&G::ScalarField::zero())
+        };
+        let with_words = |words: &[(usize, Vec<u8>)]| {
+            let mut hostile = calldata.clone();
+            for (index, value) in words {
+                hostile[4 + index * 32..][..32].copy_from_slice(value);
+            }
+            hostile
+        };
+        let argument = |index: usize| calldata[4 + index * 32..][..32].to_vec();
+        let plus_one = |index: usize| {
+            word(&(G::BaseField::from_be_bytes_mod_order(&argument(index)) + G::BaseField::one()))
+        };
+        // Off-curve `vk` and `Z0`
+        assert!(rejects(with_words(&[(1, plus_one(1))])));
+        assert!(rejects(with_words(&[(8, plus_one(8))])));
+        // Swapped and negated hints
+        let hints = G::hints(&vk, &signature);
+        if let [r0, r1] = &hints[..] {
+            let (r0, r1) = (encode_point::<G>(r0), encode_point::<G>(r1));
+            let (r0x, r0y, r1x, r1y) = (&r0[..32], &r0[32..], &r1[..32], &r1[32..]);
+            assert!(rejects(with_words(&[
+                (9, r1x.to_vec()),
+                (10, r1y.to_vec()),
+                (11, r0x.to_vec()),
+                (12, r0y.to_vec()),
+            ])));
+            let negate = |y: &[u8]| word(&-G::BaseField::from_be_bytes_mod_order(y));
+            assert!(rejects(with_words(&[(10, negate(r0y))])));
+            assert!(rejects(with_words(&[(12, negate(r1y))])));
+        }
+        // The message and the scalars of the signature at N, and at their value plus N when it
+        // fits in a word, which the contract would otherwise reduce to a valid signature
+        let modulus = G::ScalarField::MODULUS.to_bytes_be();
+        for index in 2..7 {
+            assert!(rejects(with_words(&[(index, modulus.clone())])));
+            let mut sum = argument(index);
+            let mut carry = 0u16;
+            for (byte, m) in sum.iter_mut().zip(&modulus).rev() {
+                let digit = *byte as u16 + *m as u16 + carry;
+                (*byte, carry) = (digit as u8, digit >> 8);
+            }
+            if carry == 0 {
+                assert!(rejects(with_words(&[(index, sum)])));
+            }
+        }
+    }
+
+    #[test]
+    fn test_contracts_in_evm() {
+        #[cfg(feature = "secp256k1")]
+        check_contract::<crate::curves::secp256k1::Projective>();
+        #[cfg(feature = "bn254")]
+        check_contract::<crate::curves::Bn254>();
+    }
+
+    /// The `solc` release that compiles the fixtures, so that regenerating them is reproducible.
+    const SOLC_VERSION: &str = "0.8.28";
+
+    /// Writes the verifier to `tests/fixtures` and compiles it with `solc` [`SOLC_VERSION`], or
+    /// `$SOLC`.
+    fn update_contract_fixture<G: EvmCurve>() {
+        let solc = std::env::var("SOLC").unwrap_or("solc".into());
+        let version = std::process::Command::new(&solc)
+            .arg("--version")
+            .output()
+            .expect("solc");
+        let version = String::from_utf8_lossy(&version.stdout);
+        assert!(
+            version.contains(&format!("Version: {SOLC_VERSION}+")),
+            "the fixtures are compiled with solc {SOLC_VERSION}, not {version}"
+        );
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let source = fixtures.join(format!("AN23Verifier_{}.sol", G::NAME));
+        std::fs::write(&source, verifier_contract::<G>("AN23Verifier")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let output = std::process::Command::new(&solc)
+            .args(["--optimize", "--bin-runtime", "-o"])
+            .arg(dir.path())
+            .arg(&source)
+            .output()
+            .expect("solc");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        std::fs::copy(
+            dir.path().join("AN23Verifier.bin-runtime"),
+            fixtures.join(format!("AN23Verifier_{}.bin-runtime", G::NAME)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs solc 0.8.28, regenerates tests/fixtures"]
+    fn update_contract_fixtures() {
+        #[cfg(feature = "secp256k1")]
+        update_contract_fixture::<crate::curves::secp256k1::Projective>();
+        #[cfg(feature = "bn254")]
+        update_contract_fixture::<crate::curves::Bn254>();
+    }
+}
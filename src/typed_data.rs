@@ -0,0 +1,532 @@
+//! Typed structured-data signing, modeled on [EIP-712](https://eips.ethereum.org/EIPS/eip-712).
+//!
+//! A [`Schema`] declares struct types, a [`TypedData`] holds one value of a struct type together
+//! with a [`Domain`], and [`TypedData::message`] hashes it to the scalar that gets signed:
+//!
+//! `hash_to_field(0x19 || 0x01 || hash_struct(domain) || hash_struct(message))`
+//!
+//! where `hash_struct(v) = Blake2s(type_hash || encode_data(v))` and `type_hash` is the Blake2s
+//! hash of the type's signature, e.g. `Vote(uint64 proposal,bool support)`, followed by the
+//! signatures of the struct types it references in alphabetical order. Every member is encoded
+//! into 32 bytes, little-endian like the rest of the crate; strings, bytes, arrays and structs
+//! are replaced by their hash. Signatures for different types, applications or versions can
+//! therefore never be confused.
+
+use crate::Error;
+use crate::an23_proxy_signature::{
+    AN23ProxySignature, Parameters, Signature, SigningKey, SigningToken, VerificationKey,
+    hash_to_field,
+};
+use crate::challenge_hash::ChallengeHash;
+use crate::{ProxySignature, revocation_store::RevocationStore};
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField};
+use blake2::{Blake2s256, Digest};
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// The type of a struct member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    Bool,
+    U64,
+    U128,
+    String,
+    Bytes,
+    /// An element of the scalar field of the curve.
+    Scalar,
+    /// A struct type declared in the same [`Schema`].
+    Struct(String),
+    Array(Box<FieldType>),
+}
+
+/// A value of a [`FieldType`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value<F> {
+    Bool(bool),
+    U64(u64),
+    U128(u128),
+    String(String),
+    Bytes(Vec<u8>),
+    Scalar(F),
+    /// Members in the order of the struct type.
+    Struct(Vec<(String, Value<F>)>),
+    Array(Vec<Value<F>>),
+}
+
+/// Separates applications, and versions of one application, from each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Domain {
+    pub name: String,
+    pub version: String,
+    /// Identifies the deployment, e.g. a chain or an environment.
+    pub chain_id: u64,
+}
+
+/// Declared struct types.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    types: BTreeMap<String, Vec<(String, FieldType)>>,
+}
+
+/// A struct value to sign, with its schema and domain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypedData<F> {
+    pub schema: Schema,
+    pub domain: Domain,
+    pub primary_type: String,
+    pub message: Value<F>,
+}
+
+const DOMAIN_TYPE: &str = "Domain(string name,string version,uint64 chainId)";
+
+impl FieldType {
+    fn name(&self) -> String {
+        match self {
+            FieldType::Bool => "bool".to_string(),
+            FieldType::U64 => "uint64".to_string(),
+            FieldType::U128 => "uint128".to_string(),
+            FieldType::String => "string".to_string(),
+            FieldType::Bytes => "bytes".to_string(),
+            FieldType::Scalar => "scalar".to_string(),
+            FieldType::Struct(name) => name.clone(),
+            FieldType::Array(inner) => format!("{}[]", inner.name()),
+        }
+    }
+
+    fn struct_name(&self) -> Option<&str> {
+        match self {
+            FieldType::Struct(name) => Some(name),
+            FieldType::Array(inner) => inner.struct_name(),
+            _ => None,
+        }
+    }
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the struct type `name` with the given members, in order. Type and member names
+    /// must be identifiers (ASCII letters, digits and `_`, not starting with a digit), otherwise
+    /// hashing and rendering fail with [`Error::InvalidTypedData`].
+    pub fn with_type<'a>(
+        mut self,
+        name: &str,
+        members: impl IntoIterator<Item = (&'a str, FieldType)>,
+    ) -> Self {
+        let members = members
+            .into_iter()
+            .map(|(name, ty)| (name.to_string(), ty))
+            .collect();
+        self.types.insert(name.to_string(), members);
+        self
+    }
+
+    /// The signature of `name` followed by those of the struct types it references, e.g.
+    /// `Vote(uint64 proposal,Person voter)Person(string name)`.
+    pub fn encode_type(&self, name: &str) -> Result<String, Error> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(name, &mut dependencies)?;
+        dependencies.remove(name);
+
+        let mut encoded = self.signature(name)?;
+        for dependency in dependencies {
+            encoded.push_str(&self.signature(dependency)?);
+        }
+        Ok(encoded)
+    }
+
+    pub fn type_hash(&self, name: &str) -> Result<[u8; 32], Error> {
+        Ok(Blake2s256::digest(self.encode_type(name)?.as_bytes()).into())
+    }
+
+    fn members(&self, name: &str) -> Result<&[(String, FieldType)], Error> {
+        self.types
+            .get(name)
+            .map(Vec::as_slice)
+            .ok_or(Error::InvalidTypedData)
+    }
+
+    fn signature(&self, name: &str) -> Result<String, Error> {
+        // Otherwise names could forge the punctuation of other signatures, or of the rendering
+        let members = self.members(name)?;
+        if !is_identifier(name) || !members.iter().all(|(member, _)| is_identifier(member)) {
+            return Err(Error::InvalidTypedData);
+        }
+        let members = members
+            .iter()
+            .map(|(member, ty)| format!("{} {member}", ty.name()))
+            .collect::<Vec<_>>();
+        Ok(format!("{name}({})", members.join(",")))
+    }
+
+    fn collect_dependencies<'a>(
+        &'a self,
+        name: &'a str,
+        dependencies: &mut BTreeSet<&'a str>,
+    ) -> Result<(), Error> {
+        if !dependencies.insert(name) {
+            return Ok(()); // Already visited, types may be recursive
+        }
+        for (_, ty) in self.members(name)? {
+            if let Some(dependency) = ty.struct_name() {
+                self.collect_dependencies(dependency, dependencies)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn hash_struct<F: PrimeField>(&self, name: &str, value: &Value<F>) -> Result<[u8; 32], Error> {
+        let Value::Struct(values) = value else {
+            return Err(Error::InvalidTypedData);
+        };
+        let members = self.members(name)?;
+        if members.len() != values.len() {
+            return Err(Error::InvalidTypedData);
+        }
+
+        let mut hasher = Blake2s256::new();
+        hasher.update(self.type_hash(name)?);
+        for ((member, ty), (value_name, value)) in members.iter().zip(values) {
+            if member != value_name {
+                return Err(Error::InvalidTypedData);
+            }
+            hasher.update(self.encode_value(ty, value)?);
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    fn encode_value<F: PrimeField>(
+        &self,
+        ty: &FieldType,
+        value: &Value<F>,
+    ) -> Result<[u8; 32], Error> {
+        let mut word = [0u8; 32];
+        match (ty, value) {
+            (FieldType::Bool, Value::Bool(b)) => word[0] = *b as u8,
+            (FieldType::U64, Value::U64(n)) => word[..8].copy_from_slice(&n.to_le_bytes()),
+            (FieldType::U128, Value::U128(n)) => word[..16].copy_from_slice(&n.to_le_bytes()),
+            (FieldType::String, Value::String(s)) => word = Blake2s256::digest(s.as_bytes()).into(),
+            (FieldType::Bytes, Value::Bytes(b)) => word = Blake2s256::digest(b).into(),
+            (FieldType::Scalar, Value::Scalar(f)) => {
+                let bytes = f.into_bigint().to_bytes_le();
+                if bytes.len() <= 32 {
+                    word[..bytes.len()].copy_from_slice(&bytes);
+                } else {
+                    word = Blake2s256::digest(bytes).into();
+                }
+            }
+            (FieldType::Struct(name), value) => word = self.hash_struct(name, value)?,
+            (FieldType::Array(inner), Value::Array(values)) => {
+                let mut hasher = Blake2s256::new();
+                for value in values {
+                    hasher.update(self.encode_value(inner, value)?);
+                }
+                word = hasher.finalize().into();
+            }
+            _ => return Err(Error::InvalidTypedData),
+        }
+        Ok(word)
+    }
+}
+
+impl Domain {
+    pub fn separator(&self) -> [u8; 32] {
+        let mut chain_id = [0u8; 32];
+        chain_id[..8].copy_from_slice(&self.chain_id.to_le_bytes());
+
+        let mut hasher = Blake2s256::new();
+        hasher.update(Blake2s256::digest(DOMAIN_TYPE.as_bytes()));
+        hasher.update(Blake2s256::digest(self.name.as_bytes()));
+        hasher.update(Blake2s256::digest(self.version.as_bytes()));
+        hasher.update(chain_id);
+        hasher.finalize().into()
+    }
+}
+
+impl<F: PrimeField> TypedData<F> {
+    /// The scalar to sign. Fails with [`Error::InvalidTypedData`] if the message does not match
+    /// the schema.
+    pub fn message(&self) -> Result<F, Error> {
+        let mut preimage = vec![0x19, 0x01];
+        preimage.extend(self.domain.separator());
+        preimage.extend(self.schema.hash_struct(&self.primary_type, &self.message)?);
+        Ok(hash_to_field(&preimage))
+    }
+
+    /// A human-readable rendering, so that a proxy can check what it is about to sign.
+    pub fn render(&self) -> Result<String, Error> {
+        let mut rendered = format!(
+            "{:?} (version {:?}, chain {})\n{} ",
+            self.domain.name, self.domain.version, self.domain.chain_id, self.primary_type
+        );
+        self.render_value(
+            &FieldType::Struct(self.primary_type.clone()),
+            &self.message,
+            0,
+            &mut rendered,
+        )?;
+        Ok(rendered)
+    }
+
+    fn render_value(
+        &self,
+        ty: &FieldType,
+        value: &Value<F>,
+        indent: usize,
+        out: &mut String,
+    ) -> Result<(), Error> {
+        // Validates while rendering, so that a proxy never sees a message that cannot be signed
+        self.schema.encode_value(ty, value)?;
+        match (ty, value) {
+            (_, Value::Bool(b)) => write!(out, "{b}").unwrap(),
+            (_, Value::U64(n)) => write!(out, "{n}").unwrap(),
+            (_, Value::U128(n)) => write!(out, "{n}").unwrap(),
+            (_, Value::String(s)) => write!(out, "{s:?}").unwrap(),
+            (_, Value::Bytes(b)) => write!(out, "0x{}", hex::encode(b)).unwrap(),
+            (_, Value::Scalar(f)) => write!(out, "{f}").unwrap(),
+            (FieldType::Struct(name), Value::Struct(values)) => {
+                out.push_str("{\n");
+                for ((member, member_type), (_, value)) in
+                    self.schema.members(name)?.iter().zip(values)
+                {
+                    write!(out, "{}{member}: ", "  ".repeat(indent + 1)).unwrap();
+                    self.render_value(member_type, value, indent + 1, out)?;
+                    out.push('\n');
+                }
+                write!(out, "{}}}", "  ".repeat(indent)).unwrap();
+            }
+            (FieldType::Array(inner), Value::Array(values)) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.render_value(inner, value, indent, out)?;
+                }
+                out.push(']');
+            }
+            _ => return Err(Error::InvalidTypedData),
+        }
+        Ok(())
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl<G: CurveGroup, H: ChallengeHash<G>> AN23ProxySignature<G, H>
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    /// Signs `data` with the signing key, see [`ProxySignature::sign`].
+    pub fn sign_typed<R: Rng>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        sk: &SigningKey<G>,
+        data: &TypedData<G::ScalarField>,
+    ) -> Result<Signature<G>, Error> {
+        Self::sign(rng, parameters, sk, &data.message()?, None)
+    }
+
+    /// Signs `data` with the last token of `delegation_info`, see
+    /// [`ProxySignature::delegated_sign`].
+    pub fn delegated_sign_typed<R: Rng>(
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        delegation_info: &mut Vec<SigningToken<G>>,
+        data: &TypedData<G::ScalarField>,
+    ) -> Result<Signature<G>, Error> {
+        Self::delegated_sign(rng, parameters, delegation_info, &data.message()?)
+    }
+
+    /// Verifies a signature on `data`, see [`AN23ProxySignature::verify_with_store`].
+    pub fn verify_typed(
+        parameters: &Parameters<G>,
+        vk: &VerificationKey<G>,
+        data: &TypedData<G::ScalarField>,
+        signature: &Signature<G>,
+        rev_state: &mut impl RevocationStore<G::ScalarField>,
+    ) -> Result<bool, Error> {
+        Self::verify_with_store(parameters, vk, &data.message()?, signature, rev_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::an23_proxy_signature::DelegationSpec;
+    use crate::challenge_hash::Poseidon2;
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    fn schema() -> Schema {
+        Schema::new()
+            .with_type(
+                "Vote",
+                [
+                    ("proposal", FieldType::U64),
+                    ("support", FieldType::Bool),
+                    ("voter", FieldType::Struct("Person".to_string())),
+                    ("delegates", FieldType::Array(Box::new(FieldType::String))),
+                ],
+            )
+            .with_type("Person", [("name", FieldType::String)])
+    }
+
+    fn vote(proposal: u64) -> TypedData<Fr> {
+        TypedData {
+            schema: schema(),
+            domain: Domain {
+                name: "Voting".to_string(),
+                version: "1".to_string(),
+                chain_id: 1,
+            },
+            primary_type: "Vote".to_string(),
+            message: Value::Struct(vec![
+                ("proposal".to_string(), Value::U64(proposal)),
+                ("support".to_string(), Value::Bool(true)),
+                (
+                    "voter".to_string(),
+                    Value::Struct(vec![(
+                        "name".to_string(),
+                        Value::String("alice".to_string()),
+                    )]),
+                ),
+                (
+                    "delegates".to_string(),
+                    Value::Array(vec![Value::String("bob".to_string())]),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_encode_type() {
+        assert_eq!(
+            schema().encode_type("Vote").unwrap(),
+            "Vote(uint64 proposal,bool support,Person voter,string[] delegates)Person(string name)"
+        );
+        assert_eq!(schema().encode_type("Ballot"), Err(Error::InvalidTypedData));
+    }
+
+    #[test]
+    fn test_typed_message_separation() {
+        let data = vote(42);
+        let message = data.message().unwrap();
+        assert_ne!(message, vote(43).message().unwrap());
+
+        let mut other_domain = data.clone();
+        other_domain.domain.version = "2".to_string();
+        assert_ne!(message, other_domain.message().unwrap());
+
+        // Same values under another type name
+        let mut other_type = data.clone();
+        other_type.schema = Schema::new()
+            .with_type(
+                "Poll",
+                schema()
+                    .members("Vote")
+                    .unwrap()
+                    .iter()
+                    .map(|(n, t)| (n.as_str(), t.clone())),
+            )
+            .with_type("Person", [("name", FieldType::String)]);
+        other_type.primary_type = "Poll".to_string();
+        assert_ne!(message, other_type.message().unwrap());
+
+        let mut mismatched = data.clone();
+        mismatched.message = Value::Struct(vec![("proposal".to_string(), Value::Bool(true))]);
+        assert_eq!(mismatched.message(), Err(Error::InvalidTypedData));
+        assert_eq!(mismatched.render(), Err(Error::InvalidTypedData));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            vote(42).render().unwrap(),
+            "\"Voting\" (version \"1\", chain 1)\n\
+             Vote {\n  proposal: 42\n  support: true\n  voter: {\n    name: \"alice\"\n  }\n  delegates: [\"bob\"]\n}"
+        );
+    }
+
+    #[test]
+    fn test_names_cannot_forge_structure() {
+        let mut data = vote(42);
+        data.domain.name = "Voting\nVote {\n  proposal: 1".to_string();
+        assert!(
+            data.render()
+                .unwrap()
+                .starts_with("\"Voting\\nVote {\\n  proposal: 1\" (version")
+        );
+
+        let mut data = vote(42);
+        data.schema = data
+            .schema
+            .with_type("Person", [("name: \"bob\"\n  x", FieldType::String)]);
+        assert_eq!(data.render(), Err(Error::InvalidTypedData));
+        assert_eq!(data.message(), Err(Error::InvalidTypedData));
+
+        let mut data = vote(42);
+        data.primary_type = "Vote(uint64 proposal)".to_string();
+        data.schema = schema().with_type("Vote(uint64 proposal)", [("proposal", FieldType::U64)]);
+        assert_eq!(data.message(), Err(Error::InvalidTypedData));
+        assert_eq!(
+            schema().with_type("1Vote", []).encode_type("1Vote"),
+            Err(Error::InvalidTypedData)
+        );
+    }
+
+    #[test]
+    fn test_delegated_sign_typed() {
+        // Any challenge hash, e.g. Poseidon2 for `verify_poseidon2` in Noir
+        type Scheme = AN23ProxySignature<Projective, Poseidon2>;
+        let mut rng = test_rng();
+        let parameters = Scheme::setup(&mut rng).unwrap();
+        let (sk, vk) = Scheme::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, _) = Scheme::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+
+        let signature =
+            Scheme::delegated_sign_typed(&mut rng, &parameters, &mut tokens, &vote(42)).unwrap();
+        assert!(
+            !Scheme::verify_typed(&parameters, &vk, &vote(43), &signature, &mut Vec::new())
+                .unwrap()
+        );
+        assert!(
+            Scheme::verify_typed(&parameters, &vk, &vote(42), &signature, &mut Vec::new()).unwrap()
+        );
+        assert!(
+            !AN23ProxySignature::<Projective>::verify_typed(
+                &parameters,
+                &vk,
+                &vote(42),
+                &signature,
+                &mut Vec::new()
+            )
+            .unwrap()
+        );
+
+        let signature = Scheme::sign_typed(&mut rng, &parameters, &sk, &vote(7)).unwrap();
+        assert!(
+            Scheme::verify_typed(&parameters, &vk, &vote(7), &signature, &mut Vec::new()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Proxy-side storage of delegation tokens.
+//!
+//! A [`TokenWallet`] keeps the number of consumed tokens in a small sidecar file next to the
+//! delegation bundle. The counter is durably updated *before* a signature is released, so a
+//! crash can at worst burn a token, never hand it out twice. The wallet holds an exclusive lock
+//! on the bundle while open, so two processes cannot hand out the same token either.
+
+use crate::an23_proxy_signature::{AN23ProxySignature, Parameters, Signature, SigningToken};
+use crate::challenge_hash::{Blake2s, ChallengeHash};
+use crate::{Error, ProxySignature};
+use ark_ec::CurveGroup;
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::Rng;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A delegation as handed to a proxy.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct DelegationBundle<G: CurveGroup> {
+    pub tokens: Vec<SigningToken<G>>,
+    /// Unix time in seconds from which the proxy must stop signing, if any.
+    pub expires_at: Option<u64>,
+}
+
+impl<G: CurveGroup> DelegationBundle<G> {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes)
+            .map_err(|_| Error::SerializationFailed)?;
+        write_durably(path.as_ref(), &bytes)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(|_| Error::StorageFailed)?;
+        Self::deserialize_compressed(&*bytes).map_err(|_| Error::SerializationFailed)
+    }
+
+    /// Identifies the bundle in its consumption record: the `m0` of its first token, or zero
+    /// for an empty bundle.
+    fn id(&self) -> G::ScalarField {
+        self.tokens
+            .first()
+            .map_or_else(G::ScalarField::zero, |token| token.m0)
+    }
+
+    /// Whether the proxy must stop signing at Unix time `now`, in seconds.
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// A wallet for tokens delegated with `AN23ProxySignature<G, H>`, which signs with the same
+/// challenge hash `H`.
+pub struct TokenWallet<G: CurveGroup, H = Blake2s> {
+    bundle: DelegationBundle<G>,
+    spent: u64,
+    spent_path: PathBuf,
+    /// The bundle file, exclusively locked until the wallet is dropped.
+    _lock: File,
+    _hash: PhantomData<H>,
+}
+
+impl<G: CurveGroup, H: ChallengeHash<G>> TokenWallet<G, H>
+where
+    G::ScalarField: PrimeField,
+    G::BaseField: PrimeField,
+{
+    /// Opens the bundle at `path` together with its consumption record `<path>.spent`.
+    ///
+    /// The bundle stays exclusively locked until the wallet is dropped; opening it again, from
+    /// this process or another one, fails with [`Error::StorageFailed`] in the meantime. So does
+    /// a consumption record written for a different bundle.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut lock = File::open(path.as_ref()).map_err(|_| Error::StorageFailed)?;
+        lock.try_lock().map_err(|_| Error::StorageFailed)?;
+        let mut bytes = Vec::new();
+        lock.read_to_end(&mut bytes)
+            .map_err(|_| Error::StorageFailed)?;
+        let bundle = DelegationBundle::<G>::deserialize_compressed(&*bytes)
+            .map_err(|_| Error::SerializationFailed)?;
+
+        let mut spent_path = path.as_ref().as_os_str().to_owned();
+        spent_path.push(".spent");
+        let spent_path = PathBuf::from(spent_path);
+
+        let spent = match fs::read(&spent_path) {
+            Ok(bytes) => {
+                let (bundle_id, spent) = <(G::ScalarField, u64)>::deserialize_compressed(&*bytes)
+                    .map_err(|_| Error::SerializationFailed)?;
+                if bundle_id != bundle.id() {
+                    return Err(Error::StorageFailed);
+                }
+                spent
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(_) => return Err(Error::StorageFailed),
+        };
+        if spent > bundle.tokens.len() as u64 {
+            return Err(Error::StorageFailed);
+        }
+
+        Ok(Self {
+            bundle,
+            spent,
+            spent_path,
+            _lock: lock,
+            _hash: PhantomData,
+        })
+    }
+
+    /// Number of tokens that can still be used.
+    pub fn remaining(&self) -> u64 {
+        self.bundle.tokens.len() as u64 - self.spent
+    }
+
+    pub fn expires_at(&self) -> Option<u64> {
+        self.bundle.expires_at
+    }
+
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.bundle.is_expired_at(now)
+    }
+
+    /// Signs `message` with the next unused token, see [`TokenWallet::sign_at`].
+    pub fn sign<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        message: &G::ScalarField,
+    ) -> Result<Signature<G>, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs());
+        self.sign_at(rng, parameters, message, now)
+    }
+
+    /// Signs `message` with the next unused token, given the current Unix time in seconds.
+    /// The token is durably marked as used before the signature is returned.
+    pub fn sign_at<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        parameters: &Parameters<G>,
+        message: &G::ScalarField,
+        now: u64,
+    ) -> Result<Signature<G>, Error> {
+        if self.is_expired_at(now) {
+            return Err(Error::DelegationExpired);
+        }
+        if self.remaining() == 0 {
+            return Err(Error::NoDelegationToken);
+        }
+
+        // Tokens are consumed from the back, like `delegated_sign` does
+        let index = (self.remaining() - 1) as usize;
+        let signing_token = self.bundle.tokens[index].clone();
+
+        let mut spent_bytes = Vec::new();
+        (self.bundle.id(), self.spent + 1)
+            .serialize_compressed(&mut spent_bytes)
+            .map_err(|_| Error::SerializationFailed)?;
+        write_durably(&self.spent_path, &spent_bytes)?;
+        self.spent += 1;
+
+        AN23ProxySignature::<G, H>::delegated_sign(
+            rng,
+            parameters,
+            &mut vec![signing_token],
+            message,
+        )
+    }
+}
+
+/// Replaces the file at `path` atomically: the new contents are written and synced to a
+/// temporary file, which is then renamed over `path`.
+pub fn write_durably(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    replace_file(path, bytes, false)
+}
+
+/// Like [`write_durably`], but the new file is readable and writable by its owner only from the
+/// moment it is created, so secrets never sit in a file that others can open.
+pub fn write_durably_private(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    replace_file(path, bytes, true)
+}
+
+fn replace_file(path: &Path, bytes: &[u8], private: bool) -> Result<(), Error> {
+    // Unique per process and call, so concurrent writers never share a temporary file
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    let mut file = options.open(&tmp_path).map_err(|_| Error::StorageFailed)?;
+    if file.write_all(bytes).and_then(|_| file.sync_all()).is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(Error::StorageFailed);
+    }
+    fs::rename(&tmp_path, path).map_err(|_| Error::StorageFailed)?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|_| Error::StorageFailed)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::an23_proxy_signature::DelegationSpec;
+    use ark_ff::UniformRand;
+    use ark_grumpkin::{Fr, Projective};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_wallet_signs_with_the_delegation_hash() {
+        type Scheme = AN23ProxySignature<Projective, crate::challenge_hash::Sha256>;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("delegation.bin");
+
+        let mut rng = test_rng();
+        let parameters = Scheme::setup(&mut rng).unwrap();
+        let (sk, vk) = Scheme::keygen(&mut rng, &parameters).unwrap();
+        let (tokens, _) = Scheme::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 1,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        DelegationBundle {
+            tokens,
+            expires_at: None,
+        }
+        .save(&path)
+        .unwrap();
+
+        let mut wallet =
+            TokenWallet::<Projective, crate::challenge_hash::Sha256>::open(&path).unwrap();
+        let message = Fr::rand(&mut rng);
+        let signature = wallet.sign_at(&mut rng, &parameters, &message, 0).unwrap();
+        assert!(Scheme::verify(&parameters, &vk, &message, &signature, &mut Vec::new()).unwrap());
+    }
+
+    #[test]
+    fn test_wallet_consumes_tokens_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("delegation.bin");
+
+        let mut rng = test_rng();
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let (sk, vk) = AN23ProxySignature::<Projective>::keygen(&mut rng, &parameters).unwrap();
+        let (tokens, _) = AN23ProxySignature::<Projective>::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 2,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        DelegationBundle {
+            tokens,
+            expires_at: Some(1_000),
+        }
+        .save(&path)
+        .unwrap();
+
+        let mut rev_state = Vec::new();
+        let mut wallet = TokenWallet::<Projective>::open(&path).unwrap();
+        assert_eq!(wallet.remaining(), 2);
+        let message = Fr::rand(&mut rng);
+        let signature = wallet.sign_at(&mut rng, &parameters, &message, 0).unwrap();
+        assert!(
+            AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut rev_state,
+            )
+            .unwrap()
+        );
+
+        // Simulate a restart: the used token must not be handed out again
+        drop(wallet);
+        let mut wallet = TokenWallet::<Projective>::open(&path).unwrap();
+        assert_eq!(wallet.remaining(), 1);
+        assert!(matches!(
+            wallet.sign_at(&mut rng, &parameters, &message, 1_000),
+            Err(Error::DelegationExpired)
+        ));
+        let message = Fr::rand(&mut rng);
+        let signature = wallet
+            .sign_at(&mut rng, &parameters, &message, 999)
+            .unwrap();
+        assert!(
+            AN23ProxySignature::<Projective>::verify(
+                &parameters,
+                &vk,
+                &message,
+                &signature,
+                &mut rev_state,
+            )
+            .unwrap()
+        );
+
+        assert_eq!(wallet.remaining(), 0);
+        assert!(matches!(
+            wallet.sign_at(&mut rng, &parameters, &message, 0),
+            Err(Error::NoDelegationToken)
+        ));
+    }
+
+    fn save_bundle<R: Rng>(rng: &mut R, path: &Path, number_of_tokens: u64) {
+        let parameters = AN23ProxySignature::<Projective>::setup(rng).unwrap();
+        let (sk, _) = AN23ProxySignature::<Projective>::keygen(rng, &parameters).unwrap();
+        let (tokens, _) = AN23ProxySignature::<Projective>::delegate(
+            rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        DelegationBundle {
+            tokens,
+            expires_at: None,
+        }
+        .save(path)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_wallet_is_locked_while_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("delegation.bin");
+        save_bundle(&mut test_rng(), &path, 1);
+
+        let wallet = TokenWallet::<Projective>::open(&path).unwrap();
+        assert!(matches!(
+            TokenWallet::<Projective>::open(&path),
+            Err(Error::StorageFailed)
+        ));
+        drop(wallet);
+        TokenWallet::<Projective>::open(&path).unwrap();
+    }
+
+    #[test]
+    fn test_spent_record_is_bound_to_its_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("delegation.bin");
+        let mut rng = test_rng();
+        save_bundle(&mut rng, &path, 2);
+
+        let parameters = AN23ProxySignature::<Projective>::setup(&mut rng).unwrap();
+        let mut wallet = TokenWallet::<Projective>::open(&path).unwrap();
+        let message = Fr::rand(&mut rng);
+        wallet.sign_at(&mut rng, &parameters, &message, 0).unwrap();
+        drop(wallet);
+        // Only the record itself is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // A fresh bundle at the same path must not inherit the old record
+        let other = dir.path().join("other.bin");
+        save_bundle(&mut rng, &other, 3);
+        fs::rename(&other, &path).unwrap();
+        assert!(matches!(
+            TokenWallet::<Projective>::open(&path),
+            Err(Error::StorageFailed)
+        ));
+    }
+}
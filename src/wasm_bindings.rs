@@ -1,10 +1,24 @@
-use crate::ProxySignature;
-use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec, Parameters, SigningKey};
+//! JavaScript bindings over Grumpkin, and over the curves of [`crate::curves`] through [`Curve`].
+//!
+//! Every function returns a `Result`, which wasm-bindgen turns into a thrown JS `Error` whose
+//! message is the name of the [`crate::Error`] variant, e.g. `UseOfRevokedToken`. Values passed
+//! in from JS are validated: scalars must be canonical and points on the curve.
+
+use crate::an23_proxy_signature::{
+    AN23ProxySignature, DelegationSpec, Parameters, SigningKey, VerificationKey,
+};
+use crate::key_derivation::{ExtendedSigningKey, parse_path};
+use crate::keystore::Keystore;
+use crate::wallet::DelegationBundle;
+use crate::{NamedCurve, ProxySignature};
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::{BigInteger, PrimeField, Zero};
 use ark_grumpkin::Fq;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::rngs::OsRng;
-use wasm_bindgen::prelude::wasm_bindgen;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, PoisonError};
+use wasm_bindgen::prelude::{JsError, wasm_bindgen};
 
 #[wasm_bindgen]
 #[derive(Clone)]
@@ -14,6 +28,13 @@ pub struct Fr {
 
 #[wasm_bindgen]
 impl Fr {
+    /// A scalar from its 32-byte little-endian encoding, which must be canonical.
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: Vec<u8>) -> Result<Fr, JsError> {
+        let fr = Fr { bytes };
+        ark_grumpkin::Fr::try_from(&fr).map(|_| fr)
+    }
+
     pub fn bytes(&self) -> Vec<u8> {
         self.bytes.clone()
     }
@@ -27,13 +48,15 @@ impl From<ark_grumpkin::Fr> for Fr {
     }
 }
 
-impl From<&Fr> for ark_grumpkin::Fr {
-    fn from(value: &Fr) -> Self {
-        let bytes = value.clone().bytes;
-        ark_grumpkin::Fr::from_le_bytes_mod_order(&bytes)
+impl TryFrom<&Fr> for ark_grumpkin::Fr {
+    type Error = JsError;
+
+    fn try_from(value: &Fr) -> Result<Self, Self::Error> {
+        field_from_bytes(&value.bytes).ok_or_else(|| JsError::new("invalid scalar"))
     }
 }
 
+/// A point in affine coordinates. The point at infinity is `(0, 0)`.
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct CurvePoint {
@@ -43,6 +66,14 @@ pub struct CurvePoint {
 
 #[wasm_bindgen]
 impl CurvePoint {
+    /// A point from the 32-byte little-endian encodings of its coordinates. The point must be on
+    /// the curve.
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: Vec<u8>, y: Vec<u8>) -> Result<CurvePoint, JsError> {
+        let point = CurvePoint { x, y };
+        ark_grumpkin::Projective::try_from(&point).map(|_| point)
+    }
+
     pub fn x(&self) -> Vec<u8> {
         self.x.clone()
     }
@@ -61,27 +92,39 @@ impl From<ark_grumpkin::Projective> for CurvePoint {
 
 impl From<ark_grumpkin::Affine> for CurvePoint {
     fn from(value: ark_grumpkin::Affine) -> Self {
-        let x = value.x().unwrap().into_bigint().to_bytes_le();
-        let y = value.y().unwrap().into_bigint().to_bytes_le();
-        CurvePoint { x, y }
+        let (x, y) = value.xy().unwrap_or((Fq::zero(), Fq::zero()));
+        CurvePoint {
+            x: x.into_bigint().to_bytes_le(),
+            y: y.into_bigint().to_bytes_le(),
+        }
     }
 }
 
-impl From<&CurvePoint> for ark_grumpkin::Projective {
-    fn from(value: &CurvePoint) -> Self {
-        let x = Fq::from_le_bytes_mod_order(&value.x);
-        let y = Fq::from_le_bytes_mod_order(&value.y);
-        ark_grumpkin::Affine::new(x, y).into()
+impl TryFrom<&CurvePoint> for ark_grumpkin::Projective {
+    type Error = JsError;
+
+    fn try_from(value: &CurvePoint) -> Result<Self, Self::Error> {
+        let invalid = || JsError::new("invalid curve point");
+        let x: Fq = field_from_bytes(&value.x).ok_or_else(invalid)?;
+        let y: Fq = field_from_bytes(&value.y).ok_or_else(invalid)?;
+        if x.is_zero() && y.is_zero() {
+            return Ok(ark_grumpkin::Projective::zero());
+        }
+
+        let point = ark_grumpkin::Affine::new_unchecked(x, y);
+        if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(invalid());
+        }
+        Ok(point.into())
     }
 }
 
 #[wasm_bindgen]
-pub fn setup() -> CurvePoint {
+pub fn setup() -> Result<CurvePoint, JsError> {
     let mut rng = OsRng;
-    AN23ProxySignature::<ark_grumpkin::Projective>::setup(&mut rng)
-        .unwrap()
-        .generator
-        .into()
+    let params =
+        AN23ProxySignature::<ark_grumpkin::Projective>::setup(&mut rng).map_err(to_js_error)?;
+    Ok(params.generator().into())
 }
 
 #[wasm_bindgen]
@@ -103,18 +146,15 @@ impl Keypair {
 }
 
 #[wasm_bindgen]
-pub fn keygen(params: &CurvePoint) -> Keypair {
+pub fn keygen(params: &CurvePoint) -> Result<Keypair, JsError> {
     let mut rng = OsRng;
-    let params: ark_grumpkin::Projective = params.into();
-    let (sk, vk) = AN23ProxySignature::<ark_grumpkin::Projective>::keygen(
-        &mut rng,
-        &Parameters { generator: params },
-    )
-    .unwrap();
-    Keypair {
+    let params = parameters(params)?;
+    let (sk, vk) = AN23ProxySignature::<ark_grumpkin::Projective>::keygen(&mut rng, &params)
+        .map_err(to_js_error)?;
+    Ok(Keypair {
         sk: sk.0.into(),
         vk: vk.into(),
-    }
+    })
 }
 
 #[wasm_bindgen]
@@ -129,6 +169,25 @@ pub struct Signature {
 
 #[wasm_bindgen]
 impl Signature {
+    /// A signature from its components, e.g. as received from a proxy.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        sigma_c0: Fr,
+        sigma_c1: Fr,
+        sigma_z1: Fr,
+        theta_m0: Fr,
+        theta_z0: CurvePoint,
+    ) -> Result<Signature, JsError> {
+        let signature = Signature {
+            sigma_c0,
+            sigma_c1,
+            sigma_z1,
+            theta_m0,
+            theta_z0,
+        };
+        crate::an23_proxy_signature::Signature::try_from(&signature).map(|_| signature)
+    }
+
     pub fn sigma_c0(&self) -> Fr {
         self.sigma_c0.clone()
     }
@@ -162,23 +221,44 @@ impl From<crate::an23_proxy_signature::Signature<ark_grumpkin::Projective>> for
     }
 }
 
+impl TryFrom<&Signature> for crate::an23_proxy_signature::Signature<ark_grumpkin::Projective> {
+    type Error = JsError;
+
+    fn try_from(sig: &Signature) -> Result<Self, Self::Error> {
+        Ok(crate::an23_proxy_signature::Signature {
+            sigma: crate::an23_proxy_signature::Sigma {
+                c0: (&sig.sigma_c0).try_into()?,
+                c1: (&sig.sigma_c1).try_into()?,
+                z1: (&sig.sigma_z1).try_into()?,
+            },
+            theta: crate::an23_proxy_signature::Theta {
+                m0: (&sig.theta_m0).try_into()?,
+                Z0: (&sig.theta_z0).try_into()?,
+            },
+        })
+    }
+}
+
 #[wasm_bindgen]
-pub fn sign(params: &CurvePoint, sk: &Fr, message: &Fr, policy: Option<u64>) -> Signature {
-    let params = Parameters {
-        generator: params.into(),
-    };
-    let sk = SigningKey::<ark_grumpkin::Projective>(sk.into());
-    let message = ark_grumpkin::Fr::from(message);
+pub fn sign(
+    params: &CurvePoint,
+    sk: &Fr,
+    message: &Fr,
+    policy: Option<u64>,
+) -> Result<Signature, JsError> {
+    let params = parameters(params)?;
+    let sk = SigningKey::<ark_grumpkin::Projective>(sk.try_into()?);
+    let message = ark_grumpkin::Fr::try_from(message)?;
     let policy = policy.map(|p| crate::an23_proxy_signature::Policy { amount: p });
-    AN23ProxySignature::<ark_grumpkin::Projective>::sign(
+    let signature = AN23ProxySignature::<ark_grumpkin::Projective>::sign(
         &mut OsRng,
         &params,
         &sk,
         &message,
         policy.as_ref(),
     )
-    .unwrap()
-    .into()
+    .map_err(to_js_error)?;
+    Ok(signature.into())
 }
 
 #[wasm_bindgen]
@@ -187,6 +267,7 @@ pub struct SigningToken {
     z0: Fr,
     c0: Fr,
     m0: Fr,
+    r1: Option<Fr>,
 }
 
 #[wasm_bindgen]
@@ -202,6 +283,10 @@ impl SigningToken {
     pub fn m0(&self) -> Fr {
         self.m0.clone()
     }
+
+    pub fn r1(&self) -> Option<Fr> {
+        self.r1.clone()
+    }
 }
 
 impl From<crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>> for SigningToken {
@@ -210,17 +295,23 @@ impl From<crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>> f
             z0: token.z0.into(),
             c0: token.c0.into(),
             m0: token.m0.into(),
+            r1: token.r1.map(Into::into),
         }
     }
 }
 
-impl From<&SigningToken> for crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective> {
-    fn from(token: &SigningToken) -> Self {
-        crate::an23_proxy_signature::SigningToken {
-            z0: ark_grumpkin::Fr::from(&token.z0),
-            c0: ark_grumpkin::Fr::from(&token.c0),
-            m0: ark_grumpkin::Fr::from(&token.m0),
-        }
+impl TryFrom<&SigningToken>
+    for crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>
+{
+    type Error = JsError;
+
+    fn try_from(token: &SigningToken) -> Result<Self, Self::Error> {
+        Ok(crate::an23_proxy_signature::SigningToken {
+            z0: (&token.z0).try_into()?,
+            c0: (&token.c0).try_into()?,
+            m0: (&token.m0).try_into()?,
+            r1: token.r1.as_ref().map(TryInto::try_into).transpose()?,
+        })
     }
 }
 
@@ -241,52 +332,1063 @@ impl DelegationRes {
     }
 }
 
+/// The encodable form of [`DelegationRes`].
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Delegation {
+    delegation_info: Vec<crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_utils::canonical"))]
+    revocation_key: Vec<ark_grumpkin::Fr>,
+}
+
+impl From<Delegation> for DelegationRes {
+    fn from(delegation: Delegation) -> Self {
+        DelegationRes {
+            delegation_info: delegation
+                .delegation_info
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            revokation_key: delegation
+                .revocation_key
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&DelegationRes> for Delegation {
+    type Error = JsError;
+
+    fn try_from(res: &DelegationRes) -> Result<Self, Self::Error> {
+        Ok(Delegation {
+            delegation_info: res
+                .delegation_info
+                .iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            revocation_key: res
+                .revokation_key
+                .iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
 #[wasm_bindgen]
-pub fn delegate(params: &CurvePoint, sk: &Fr, delegation_spec: u64) -> DelegationRes {
-    let params = Parameters {
-        generator: params.into(),
-    };
-    let sk = SigningKey::<ark_grumpkin::Projective>(sk.into());
+pub fn delegate(
+    params: &CurvePoint,
+    sk: &Fr,
+    delegation_spec: u64,
+) -> Result<DelegationRes, JsError> {
+    let params = parameters(params)?;
+    let sk = SigningKey::<ark_grumpkin::Projective>(sk.try_into()?);
     let deg_spec = DelegationSpec {
         number_of_tokens: delegation_spec,
+        bind_message_nonce: false,
     };
 
     let (delegation_info, rev_key) = AN23ProxySignature::<ark_grumpkin::Projective>::delegate(
         &mut OsRng, &params, &sk, &deg_spec,
     )
-    .unwrap();
+    .map_err(to_js_error)?;
 
-    DelegationRes {
-        delegation_info: delegation_info.into_iter().map(Into::into).collect(),
-        revokation_key: rev_key.into_iter().map(Into::into).collect(),
+    Ok(Delegation {
+        delegation_info,
+        revocation_key: rev_key,
     }
+    .into())
 }
 
+/// Signs with the last token of `delegation_info`. The caller's array is not modified, so passing
+/// it again reuses the token; prefer [`DelegationWallet`].
 #[wasm_bindgen]
 pub fn delegated_sign(
     params: &CurvePoint,
     delegation_info: Vec<SigningToken>,
     message: &Fr,
-) -> Signature {
-    let params = Parameters {
-        generator: params.into(),
-    };
-    let mut delegation_info: Vec<
-        crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>,
-    > = delegation_info.iter().map(Into::into).collect();
-    let message = ark_grumpkin::Fr::from(message);
+) -> Result<Signature, JsError> {
+    let params = parameters(params)?;
+    let mut delegation_info = delegation_info
+        .iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<_>, _>>()?;
+    let message = ark_grumpkin::Fr::try_from(message)?;
 
-    AN23ProxySignature::<ark_grumpkin::Projective>::delegated_sign(
+    let signature = AN23ProxySignature::<ark_grumpkin::Projective>::delegated_sign(
         &mut OsRng,
         &params,
         &mut delegation_info,
         &message,
     )
-    .unwrap()
-    .into()
+    .map_err(to_js_error)?;
+    Ok(signature.into())
+}
+
+/// A delegation held by a proxy, handing out one signature per token.
+///
+/// Signing takes two steps, so that a used token is never handed out again: [`DelegationWallet::reserve`]
+/// takes the next token off the wallet and returns the wallet's bytes without it, which must be
+/// persisted (e.g. in IndexedDB) before [`DelegationWallet::sign_reserved`] signs with the token.
+/// Tokens are consumed from the back, like `delegated_sign` does.
+#[wasm_bindgen]
+pub struct DelegationWallet {
+    bundle: DelegationBundle<ark_grumpkin::Projective>,
+    /// Taken off `bundle` by `reserve`, not yet signed with.
+    reserved: Option<crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>>,
+}
+
+#[wasm_bindgen]
+impl DelegationWallet {
+    /// A wallet for the tokens returned by `delegate`. `expires_at` is a Unix time in seconds.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        delegation_info: Vec<SigningToken>,
+        expires_at: Option<u64>,
+    ) -> Result<DelegationWallet, JsError> {
+        let tokens = delegation_info
+            .iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(DelegationWallet {
+            bundle: DelegationBundle { tokens, expires_at },
+            reserved: None,
+        })
+    }
+
+    /// Restores a wallet saved with [`DelegationWallet::to_bytes`] or returned by
+    /// [`DelegationWallet::reserve`]. The encoding is the same as a `DelegationBundle` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<DelegationWallet, JsError> {
+        Ok(DelegationWallet {
+            bundle: decode(bytes)?,
+            reserved: None,
+        })
+    }
+
+    /// The unused tokens, without the reserved one.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        encode(&self.bundle)
+    }
+
+    /// Number of tokens that can still be reserved.
+    pub fn remaining(&self) -> usize {
+        self.bundle.tokens.len()
+    }
+
+    pub fn expires_at(&self) -> Option<u64> {
+        self.bundle.expires_at
+    }
+
+    /// Takes the next unused token off the wallet, given the current Unix time in seconds, e.g.
+    /// `Math.floor(Date.now() / 1000)`, and returns the wallet's bytes without it. Persist them
+    /// before calling [`DelegationWallet::sign_reserved`]. Throws `NoDelegationToken` once all
+    /// tokens are used and `DelegationExpired` after the expiry. A token that is already reserved
+    /// stays reserved.
+    pub fn reserve(&mut self, now: u64) -> Result<Vec<u8>, JsError> {
+        if self.bundle.is_expired_at(now) {
+            return Err(to_js_error(crate::Error::DelegationExpired));
+        }
+        if self.reserved.is_none() {
+            let token = self
+                .bundle
+                .tokens
+                .pop()
+                .ok_or_else(|| to_js_error(crate::Error::NoDelegationToken))?;
+            self.reserved = Some(token);
+        }
+        self.to_bytes()
+    }
+
+    /// Signs `message` with the token taken by [`DelegationWallet::reserve`], given the current
+    /// Unix time in seconds. Throws `NoDelegationToken` if no token is reserved and
+    /// `DelegationExpired` after the expiry, in which case the token stays used.
+    pub fn sign_reserved(
+        &mut self,
+        params: &CurvePoint,
+        message: &Fr,
+        now: u64,
+    ) -> Result<Signature, JsError> {
+        let params = parameters(params)?;
+        let message = ark_grumpkin::Fr::try_from(message)?;
+        let token = self
+            .reserved
+            .take()
+            .ok_or_else(|| to_js_error(crate::Error::NoDelegationToken))?;
+        if self.bundle.is_expired_at(now) {
+            return Err(to_js_error(crate::Error::DelegationExpired));
+        }
+
+        let signature = AN23ProxySignature::<ark_grumpkin::Projective>::delegated_sign(
+            &mut OsRng,
+            &params,
+            &mut vec![token],
+            &message,
+        )
+        .map_err(to_js_error)?;
+        Ok(signature.into())
+    }
+}
+
+/// The `m0`s of spent or revoked tokens, owned by JS.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct RevocationState {
+    entries: Vec<ark_grumpkin::Fr>,
+}
+
+#[wasm_bindgen]
+impl RevocationState {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RevocationState {
+        RevocationState::default()
+    }
+
+    /// Restores a state saved with [`RevocationState::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<RevocationState, JsError> {
+        Ok(RevocationState {
+            entries: decode(bytes)?,
+        })
+    }
+
+    /// The compressed canonical encoding, the same as the CLI's binary revocation state.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        encode(&self.entries)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, m0: &Fr) -> Result<bool, JsError> {
+        Ok(self.entries.contains(&m0.try_into()?))
+    }
+}
+
+/// Verifies `signature` and records its token in `rev_state`. Returns `false` for an invalid
+/// signature and throws `UseOfRevokedToken` if the token was revoked or already used.
+#[wasm_bindgen]
+pub fn verify(
+    params: &CurvePoint,
+    vk: &CurvePoint,
+    message: &Fr,
+    signature: &Signature,
+    rev_state: &mut RevocationState,
+) -> Result<bool, JsError> {
+    let params = parameters(params)?;
+    let vk = ark_grumpkin::Projective::try_from(vk)?.into_affine();
+    let message = ark_grumpkin::Fr::try_from(message)?;
+    let signature = signature.try_into()?;
+    AN23ProxySignature::<ark_grumpkin::Projective>::verify(
+        &params,
+        &vk,
+        &message,
+        &signature,
+        &mut rev_state.entries,
+    )
+    .map_err(to_js_error)
+}
+
+/// Adds the revocation key of a delegation to `rev_state`.
+#[wasm_bindgen]
+pub fn revoke(rev_key: Vec<Fr>, rev_state: &mut RevocationState) -> Result<(), JsError> {
+    let rev_key = rev_key
+        .iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<_>, _>>()?;
+    AN23ProxySignature::<ark_grumpkin::Projective>::revoke_in_store(
+        &rev_key,
+        &mut rev_state.entries,
+    )
+    .map_err(to_js_error)
+}
+
+/// A payment through the Aztec token contract, see [`crate::aztec::TransferIntent`]. Sign
+/// [`TransferIntent::message`] and pass [`TransferIntent::public_transfer_args`] to the contract.
+#[wasm_bindgen]
+pub struct TransferIntent {
+    intent: crate::aztec::TransferIntent,
+}
+
+#[wasm_bindgen]
+impl TransferIntent {
+    /// Addresses and the nonce are hex field elements, as printed by aztec.js.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        token_contract: &str,
+        to: &str,
+        amount: u128,
+        nonce: &str,
+    ) -> Result<TransferIntent, JsError> {
+        let field = |hex| crate::aztec::field_from_hex(hex).map_err(to_js_error);
+        Ok(TransferIntent {
+            intent: crate::aztec::TransferIntent {
+                to: field(to)?,
+                amount,
+                nonce: field(nonce)?,
+                token_contract: field(token_contract)?,
+            },
+        })
+    }
+
+    /// The message to sign for this transfer.
+    pub fn message(&self) -> Fr {
+        self.intent.message().into()
+    }
+
+    /// The arguments of the contract's `public_transfer`, in order, as `0x`-prefixed hex fields.
+    pub fn public_transfer_args(&self, signature: &Signature) -> Result<Vec<String>, JsError> {
+        let fields = self.public_transfer(signature)?;
+        Ok(fields.iter().map(crate::aztec::field_to_hex).collect())
+    }
+
+    /// Like [`TransferIntent::public_transfer_args`], as a JSON array.
+    pub fn public_transfer_args_json(&self, signature: &Signature) -> Result<String, JsError> {
+        let fields = self.public_transfer(signature)?;
+        Ok(crate::aztec::fields_to_json(&fields))
+    }
+
+    fn public_transfer(&self, signature: &Signature) -> Result<Vec<Fq>, JsError> {
+        let signature = signature.try_into()?;
+        crate::aztec::PublicTransfer {
+            intent: &self.intent,
+            signature: &signature,
+        }
+        .to_fields()
+        .map_err(to_js_error)
+    }
 }
 
 #[wasm_bindgen]
 pub fn hash_to_field(message: &[u8]) -> Fr {
     crate::an23_proxy_signature::hash_to_field::<ark_grumpkin::Fr>(message).into()
 }
+
+/// Encrypts a signing key with a password, see [`crate::keystore::Keystore`].
+#[wasm_bindgen]
+pub fn encrypt_signing_key(
+    params: &CurvePoint,
+    sk: &Fr,
+    password: &str,
+    created_at: u64,
+) -> Result<Vec<u8>, JsError> {
+    let params = parameters(params)?;
+    let sk = SigningKey::<ark_grumpkin::Projective>(sk.try_into()?);
+    let keystore = Keystore::encrypt(&mut OsRng, &params, &sk, password.as_bytes(), created_at)
+        .map_err(to_js_error)?;
+    encode(&keystore)
+}
+
+#[wasm_bindgen]
+pub fn decrypt_signing_key(
+    params: &CurvePoint,
+    keystore: &[u8],
+    password: &str,
+) -> Result<Fr, JsError> {
+    let params = parameters(params)?;
+    let keystore: Keystore = decode(keystore)?;
+    let sk = keystore
+        .decrypt(&params, password.as_bytes())
+        .map_err(to_js_error)?;
+    Ok(sk.0.into())
+}
+
+#[wasm_bindgen]
+pub fn change_keystore_password(
+    keystore: &[u8],
+    old_password: &str,
+    new_password: &str,
+) -> Result<Vec<u8>, JsError> {
+    let keystore: Keystore = decode(keystore)?;
+    let keystore = keystore
+        .change_password(&mut OsRng, old_password.as_bytes(), new_password.as_bytes())
+        .map_err(to_js_error)?;
+    encode(&keystore)
+}
+
+/// The verification key stored in a keystore, readable without the password.
+#[wasm_bindgen]
+pub fn keystore_verification_key(keystore: &[u8]) -> Result<CurvePoint, JsError> {
+    let keystore: Keystore = decode(keystore)?;
+    let vk = keystore
+        .verification_key::<ark_grumpkin::Projective>()
+        .map_err(to_js_error)?;
+    Ok(vk.into())
+}
+
+/// The mnemonic backup of a signing key, see [`SigningKey::to_mnemonic`].
+#[wasm_bindgen]
+pub fn signing_key_to_mnemonic(sk: &Fr) -> Result<String, JsError> {
+    SigningKey::<ark_grumpkin::Projective>(sk.try_into()?)
+        .to_mnemonic()
+        .map_err(to_js_error)
+}
+
+#[wasm_bindgen]
+pub fn signing_key_from_mnemonic(phrase: &str) -> Result<Fr, JsError> {
+    let sk = SigningKey::<ark_grumpkin::Projective>::from_mnemonic(phrase).map_err(to_js_error)?;
+    Ok(sk.0.into())
+}
+
+/// Derives the child signing key at `path`, e.g. `m/1'/0`, see [`crate::key_derivation`].
+#[wasm_bindgen]
+pub fn derive_signing_key(params: &CurvePoint, sk: &Fr, path: &str) -> Result<Fr, JsError> {
+    let params = parameters(params)?;
+    let path = parse_path(path).map_err(to_js_error)?;
+    let child = ExtendedSigningKey::<ark_grumpkin::Projective>::new(SigningKey(sk.try_into()?))
+        .derive_path(&params, &path)
+        .map_err(to_js_error)?;
+    Ok(child.signing_key.0.into())
+}
+
+/// The curves of [`Curve`], depending on the enabled cargo features.
+#[derive(Clone, Copy)]
+enum CurveName {
+    Grumpkin,
+    #[cfg(feature = "secp256k1")]
+    Secp256k1,
+    #[cfg(feature = "bn254")]
+    Bn254,
+    #[cfg(feature = "pasta")]
+    Pallas,
+    #[cfg(feature = "pasta")]
+    Vesta,
+    #[cfg(feature = "ristretto")]
+    Ristretto,
+}
+
+impl CurveName {
+    const ALL: &[CurveName] = &[
+        CurveName::Grumpkin,
+        #[cfg(feature = "secp256k1")]
+        CurveName::Secp256k1,
+        #[cfg(feature = "bn254")]
+        CurveName::Bn254,
+        #[cfg(feature = "pasta")]
+        CurveName::Pallas,
+        #[cfg(feature = "pasta")]
+        CurveName::Vesta,
+        #[cfg(feature = "ristretto")]
+        CurveName::Ristretto,
+    ];
+}
+
+/// Calls `$f::<G>($args)` with the group `G` of a [`CurveName`].
+macro_rules! on_curve {
+    ($name:expr, $f:ident($($arg:expr),*)) => {
+        match $name {
+            CurveName::Grumpkin => $f::<ark_grumpkin::Projective>($($arg),*),
+            #[cfg(feature = "secp256k1")]
+            CurveName::Secp256k1 => $f::<crate::curves::secp256k1::Projective>($($arg),*),
+            #[cfg(feature = "bn254")]
+            CurveName::Bn254 => $f::<crate::curves::Bn254>($($arg),*),
+            #[cfg(feature = "pasta")]
+            CurveName::Pallas => $f::<crate::curves::pasta::pallas::Projective>($($arg),*),
+            #[cfg(feature = "pasta")]
+            CurveName::Vesta => $f::<crate::curves::pasta::vesta::Projective>($($arg),*),
+            #[cfg(feature = "ristretto")]
+            CurveName::Ristretto => $f::<crate::curves::ristretto::Projective>($($arg),*),
+        }
+    };
+}
+
+/// The scheme over a curve chosen at run time, see [`crate::curves`]. Every value is passed as
+/// its compressed canonical encoding, as in the Rust API and the CLI's binary encoding, and
+/// parameters, keys and signatures of one curve do not decode on another.
+#[wasm_bindgen]
+pub struct Curve {
+    name: CurveName,
+}
+
+#[wasm_bindgen]
+impl Curve {
+    /// `name` is one of [`Curve::names`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> Result<Curve, JsError> {
+        CurveName::ALL
+            .iter()
+            .find(|curve| on_curve!(**curve, curve_name()) == name)
+            .map(|&name| Curve { name })
+            .ok_or_else(|| JsError::new(&format!("unsupported curve: {name}")))
+    }
+
+    /// The curves of this build.
+    pub fn names() -> Vec<String> {
+        CurveName::ALL
+            .iter()
+            .map(|curve| on_curve!(*curve, curve_name()).to_string())
+            .collect()
+    }
+
+    pub fn name(&self) -> String {
+        on_curve!(self.name, curve_name()).to_string()
+    }
+
+    /// Encoded parameters.
+    pub fn setup(&self) -> Result<Vec<u8>, JsError> {
+        on_curve!(self.name, curve_setup())
+    }
+
+    pub fn keygen(&self, params: &[u8]) -> Result<EncodedKeypair, JsError> {
+        on_curve!(self.name, curve_keygen(params))
+    }
+
+    /// A delegation of `number_of_tokens` tokens: the bundle for the proxy, in the encoding of
+    /// [`DelegationWallet::to_bytes`], and the revocation key.
+    pub fn delegate(
+        &self,
+        params: &[u8],
+        sk: &[u8],
+        number_of_tokens: u64,
+        expires_at: Option<u64>,
+    ) -> Result<EncodedDelegation, JsError> {
+        on_curve!(
+            self.name,
+            curve_delegate(params, sk, number_of_tokens, expires_at)
+        )
+    }
+
+    /// Signs with the last token of `bundle`, given the current Unix time in seconds like
+    /// [`DelegationWallet::reserve`], and returns the signature and the bundle without that
+    /// token, which the caller must persist in place of `bundle`. Throws `DelegationExpired`
+    /// after the expiry of `bundle`.
+    pub fn delegated_sign(
+        &self,
+        params: &[u8],
+        bundle: &[u8],
+        message: &[u8],
+        now: u64,
+    ) -> Result<EncodedSignature, JsError> {
+        on_curve!(
+            self.name,
+            curve_delegated_sign(params, bundle, message, now)
+        )
+    }
+
+    /// Verifies `signature` against the encoded revocation state `rev_state`. Returns
+    /// `undefined` for an invalid signature, or the state with the token recorded, and throws
+    /// `UseOfRevokedToken` if the token was revoked or already used.
+    pub fn verify(
+        &self,
+        params: &[u8],
+        vk: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        rev_state: &[u8],
+    ) -> Result<Option<Vec<u8>>, JsError> {
+        on_curve!(
+            self.name,
+            curve_verify(params, vk, message, signature, rev_state)
+        )
+    }
+
+    /// The revocation state `rev_state` with the tokens of `rev_key` added.
+    pub fn revoke(&self, rev_key: &[u8], rev_state: &[u8]) -> Result<Vec<u8>, JsError> {
+        on_curve!(self.name, curve_revoke(rev_key, rev_state))
+    }
+
+    /// An empty revocation state.
+    pub fn empty_revocation_state(&self) -> Result<Vec<u8>, JsError> {
+        on_curve!(self.name, curve_empty_revocation_state())
+    }
+
+    /// The encoded scalar of [`hash_to_field`] on this curve.
+    pub fn hash_to_field(&self, message: &[u8]) -> Result<Vec<u8>, JsError> {
+        on_curve!(self.name, curve_hash_to_field(message))
+    }
+}
+
+#[wasm_bindgen]
+pub struct EncodedKeypair {
+    sk: Vec<u8>,
+    vk: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl EncodedKeypair {
+    pub fn sk(&self) -> Vec<u8> {
+        self.sk.clone()
+    }
+
+    pub fn vk(&self) -> Vec<u8> {
+        self.vk.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct EncodedDelegation {
+    bundle: Vec<u8>,
+    revocation_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl EncodedDelegation {
+    pub fn bundle(&self) -> Vec<u8> {
+        self.bundle.clone()
+    }
+
+    pub fn revocation_key(&self) -> Vec<u8> {
+        self.revocation_key.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct EncodedSignature {
+    signature: Vec<u8>,
+    bundle: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl EncodedSignature {
+    pub fn signature(&self) -> Vec<u8> {
+        self.signature.clone()
+    }
+
+    /// The remaining tokens.
+    pub fn bundle(&self) -> Vec<u8> {
+        self.bundle.clone()
+    }
+}
+
+fn curve_name<G: NamedCurve>() -> &'static str {
+    G::NAME
+}
+
+fn curve_setup<G: CurveGroup>() -> Result<Vec<u8>, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    encode(&AN23ProxySignature::<G>::setup(&mut OsRng).map_err(to_js_error)?)
+}
+
+fn curve_keygen<G: CurveGroup>(params: &[u8]) -> Result<EncodedKeypair, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    let params = curve_parameters::<G>(params)?;
+    let (sk, vk) = AN23ProxySignature::<G>::keygen(&mut OsRng, &params).map_err(to_js_error)?;
+    Ok(EncodedKeypair {
+        sk: encode(&sk)?,
+        vk: encode(&vk)?,
+    })
+}
+
+fn curve_delegate<G: CurveGroup>(
+    params: &[u8],
+    sk: &[u8],
+    number_of_tokens: u64,
+    expires_at: Option<u64>,
+) -> Result<EncodedDelegation, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    let params = curve_parameters::<G>(params)?;
+    let sk: SigningKey<G> = decode(sk)?;
+    let deg_spec = DelegationSpec {
+        number_of_tokens,
+        bind_message_nonce: false,
+    };
+    let (tokens, rev_key) = AN23ProxySignature::<G>::delegate(&mut OsRng, &params, &sk, &deg_spec)
+        .map_err(to_js_error)?;
+    Ok(EncodedDelegation {
+        bundle: encode(&DelegationBundle { tokens, expires_at })?,
+        revocation_key: encode(&rev_key)?,
+    })
+}
+
+fn curve_delegated_sign<G: CurveGroup>(
+    params: &[u8],
+    bundle: &[u8],
+    message: &[u8],
+    now: u64,
+) -> Result<EncodedSignature, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    let params = curve_parameters::<G>(params)?;
+    let mut bundle: DelegationBundle<G> = decode(bundle)?;
+    if bundle.is_expired_at(now) {
+        return Err(to_js_error(crate::Error::DelegationExpired));
+    }
+    let message: G::ScalarField = decode(message)?;
+    let signature =
+        AN23ProxySignature::<G>::delegated_sign(&mut OsRng, &params, &mut bundle.tokens, &message)
+            .map_err(to_js_error)?;
+    Ok(EncodedSignature {
+        signature: encode(&signature)?,
+        bundle: encode(&bundle)?,
+    })
+}
+
+fn curve_verify<G: CurveGroup>(
+    params: &[u8],
+    vk: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    rev_state: &[u8],
+) -> Result<Option<Vec<u8>>, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    let params = curve_parameters::<G>(params)?;
+    let vk: VerificationKey<G> = decode(vk)?;
+    let message: G::ScalarField = decode(message)?;
+    let signature: crate::an23_proxy_signature::Signature<G> = decode(signature)?;
+    let mut rev_state: Vec<G::ScalarField> = decode(rev_state)?;
+    let valid = AN23ProxySignature::<G>::verify(&params, &vk, &message, &signature, &mut rev_state)
+        .map_err(to_js_error)?;
+    valid.then(|| encode(&rev_state)).transpose()
+}
+
+fn curve_revoke<G: CurveGroup>(rev_key: &[u8], rev_state: &[u8]) -> Result<Vec<u8>, JsError>
+where
+    G::BaseField: PrimeField,
+{
+    let rev_key: Vec<G::ScalarField> = decode(rev_key)?;
+    let mut rev_state: Vec<G::ScalarField> = decode(rev_state)?;
+    AN23ProxySignature::<G>::revoke_in_store(&rev_key, &mut rev_state).map_err(to_js_error)?;
+    encode(&rev_state)
+}
+
+fn curve_empty_revocation_state<G: CurveGroup>() -> Result<Vec<u8>, JsError> {
+    encode(&Vec::<G::ScalarField>::new())
+}
+
+fn curve_hash_to_field<G: CurveGroup>(message: &[u8]) -> Result<Vec<u8>, JsError> {
+    encode(&crate::an23_proxy_signature::hash_to_field::<G::ScalarField>(message))
+}
+
+/// Adds `to_bytes`/`from_bytes` (the compressed canonical encoding of `$inner`, as used by the
+/// Rust API and the CLI) and `to_hex`/`from_hex`.
+macro_rules! impl_encodings {
+    ($name:ident, $inner:ty) => {
+        #[wasm_bindgen]
+        impl $name {
+            pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+                encode(&<$inner>::try_from(self)?)
+            }
+
+            pub fn from_bytes(bytes: &[u8]) -> Result<$name, JsError> {
+                decode::<$inner>(bytes).map(Into::into)
+            }
+
+            pub fn to_hex(&self) -> Result<String, JsError> {
+                self.to_bytes().map(hex::encode)
+            }
+
+            pub fn from_hex(hex: &str) -> Result<$name, JsError> {
+                let hex = hex.trim();
+                let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+                    .map_err(|e| JsError::new(&e.to_string()))?;
+                Self::from_bytes(&bytes)
+            }
+        }
+    };
+}
+
+/// Adds `to_json`/`from_json`: the `serde` representation of `$inner`, or with `hex`, a JSON
+/// string holding the hex encoding.
+macro_rules! impl_json {
+    ($name:ident, hex) => {
+        #[cfg(feature = "serde")]
+        #[wasm_bindgen]
+        impl $name {
+            pub fn to_json(&self) -> Result<String, JsError> {
+                serde_json::to_string(&self.to_hex()?).map_err(|e| JsError::new(&e.to_string()))
+            }
+
+            pub fn from_json(json: &str) -> Result<$name, JsError> {
+                let hex: String =
+                    serde_json::from_str(json).map_err(|e| JsError::new(&e.to_string()))?;
+                Self::from_hex(&hex)
+            }
+        }
+    };
+    ($name:ident, $inner:ty) => {
+        #[cfg(feature = "serde")]
+        #[wasm_bindgen]
+        impl $name {
+            pub fn to_json(&self) -> Result<String, JsError> {
+                serde_json::to_string(&<$inner>::try_from(self)?)
+                    .map_err(|e| JsError::new(&e.to_string()))
+            }
+
+            pub fn from_json(json: &str) -> Result<$name, JsError> {
+                let value: $inner =
+                    serde_json::from_str(json).map_err(|e| JsError::new(&e.to_string()))?;
+                Ok(value.into())
+            }
+        }
+    };
+}
+
+impl_encodings!(Fr, ark_grumpkin::Fr);
+impl_encodings!(CurvePoint, ark_grumpkin::Projective);
+impl_encodings!(
+    Signature,
+    crate::an23_proxy_signature::Signature<ark_grumpkin::Projective>
+);
+impl_encodings!(
+    SigningToken,
+    crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>
+);
+impl_encodings!(DelegationRes, Delegation);
+
+impl_json!(Fr, hex);
+impl_json!(CurvePoint, hex);
+impl_json!(
+    Signature,
+    crate::an23_proxy_signature::Signature<ark_grumpkin::Projective>
+);
+impl_json!(
+    SigningToken,
+    crate::an23_proxy_signature::SigningToken<ark_grumpkin::Projective>
+);
+impl_json!(DelegationRes, Delegation);
+
+fn encode<T: CanonicalSerialize>(value: &T) -> Result<Vec<u8>, JsError> {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Decodes and validates `bytes`, which must hold exactly one value.
+fn decode<T: CanonicalDeserialize>(mut bytes: &[u8]) -> Result<T, JsError> {
+    let value = T::deserialize_compressed(&mut bytes).map_err(|e| JsError::new(&e.to_string()))?;
+    if !bytes.is_empty() {
+        return Err(JsError::new("trailing bytes"));
+    }
+    Ok(value)
+}
+
+/// How many parameters [`cached_parameters`] keeps, oldest evicted first. Apps use a handful of
+/// generators, usually the default one of each curve.
+const PARAMETERS_CACHE_SIZE: usize = 8;
+
+type ParametersCache = Vec<(TypeId, Vec<u8>, Arc<dyn Any + Send + Sync>)>;
+
+static PARAMETERS: Mutex<ParametersCache> = Mutex::new(Vec::new());
+
+/// The parameters for `generator`, shared across calls so that the fixed-base table behind
+/// [`Parameters::mul_generator`] is built once per generator rather than on every call. The point
+/// at infinity is rejected: every key would be the identity.
+fn cached_parameters<G: CurveGroup>(generator: G) -> Result<Arc<Parameters<G>>, JsError>
+where
+    G::ScalarField: PrimeField,
+{
+    if generator.is_zero() {
+        return Err(JsError::new("invalid generator"));
+    }
+    let key = encode(&generator)?;
+    let mut cache = PARAMETERS.lock().unwrap_or_else(PoisonError::into_inner);
+    let cached = cache
+        .iter()
+        .filter(|(curve, generator, _)| *curve == TypeId::of::<G>() && *generator == key)
+        .find_map(|(_, _, parameters)| parameters.clone().downcast().ok());
+    if let Some(parameters) = cached {
+        return Ok(parameters);
+    }
+
+    let parameters = Arc::new(Parameters::new(generator));
+    if cache.len() == PARAMETERS_CACHE_SIZE {
+        cache.remove(0);
+    }
+    cache.push((TypeId::of::<G>(), key, parameters.clone()));
+    Ok(parameters)
+}
+
+fn parameters(
+    generator: &CurvePoint,
+) -> Result<Arc<Parameters<ark_grumpkin::Projective>>, JsError> {
+    cached_parameters(ark_grumpkin::Projective::try_from(generator)?)
+}
+
+fn curve_parameters<G: CurveGroup>(params: &[u8]) -> Result<Arc<Parameters<G>>, JsError>
+where
+    G::ScalarField: PrimeField,
+{
+    cached_parameters(decode::<Parameters<G>>(params)?.generator())
+}
+
+/// Parses a canonical little-endian field element.
+fn field_from_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let value = F::from_le_bytes_mod_order(bytes);
+    (value.into_bigint().to_bytes_le() == bytes).then_some(value)
+}
+
+fn to_js_error(error: crate::Error) -> JsError {
+    JsError::new(&format!("{error:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only success paths: `JsError` cannot be constructed outside of wasm
+    #[test]
+    fn test_verify_and_revoke() {
+        let params = setup().unwrap();
+        let keypair = keygen(&params).unwrap();
+        let delegation = delegate(&params, &keypair.sk(), 2).unwrap();
+        let message = hash_to_field(b"pay 10 to bob");
+
+        let signature = delegated_sign(&params, delegation.delegation_info(), &message).unwrap();
+        let signature = Signature::new(
+            signature.sigma_c0(),
+            signature.sigma_c1(),
+            signature.sigma_z1(),
+            signature.theta_m0(),
+            CurvePoint::new(signature.theta_z0().x(), signature.theta_z0().y()).unwrap(),
+        )
+        .unwrap();
+
+        let mut rev_state = RevocationState::new();
+        assert!(verify(&params, &keypair.vk(), &message, &signature, &mut rev_state).unwrap());
+        assert!(rev_state.contains(&signature.theta_m0()).unwrap());
+
+        let other_message = hash_to_field(b"pay 20 to bob");
+        assert!(
+            !verify(
+                &params,
+                &keypair.vk(),
+                &other_message,
+                &signature,
+                &mut RevocationState::new()
+            )
+            .unwrap()
+        );
+
+        revoke(delegation.revokation_key(), &mut rev_state).unwrap();
+        let rev_state = RevocationState::from_bytes(&rev_state.to_bytes().unwrap()).unwrap();
+        assert_eq!(rev_state.len(), 2);
+    }
+
+    #[test]
+    fn test_delegation_wallet_consumes_tokens() {
+        let params = setup().unwrap();
+        let keypair = keygen(&params).unwrap();
+        let delegation = delegate(&params, &keypair.sk(), 2).unwrap();
+        let mut wallet = DelegationWallet::new(delegation.delegation_info(), Some(1_000)).unwrap();
+
+        let mut rev_state = RevocationState::new();
+        let message = hash_to_field(b"pay 10 to bob");
+        let persisted = wallet.reserve(0).unwrap();
+        assert_eq!(wallet.remaining(), 1);
+        // Reserving again keeps the same token
+        assert_eq!(wallet.reserve(0).unwrap(), persisted);
+        let signature = wallet.sign_reserved(&params, &message, 0).unwrap();
+        assert!(verify(&params, &keypair.vk(), &message, &signature, &mut rev_state).unwrap());
+        assert_eq!(wallet.remaining(), 1);
+
+        // The restored wallet hands out the other token
+        let mut wallet = DelegationWallet::from_bytes(&persisted).unwrap();
+        assert_eq!(wallet.expires_at(), Some(1_000));
+        wallet.reserve(999).unwrap();
+        let signature = wallet.sign_reserved(&params, &message, 999).unwrap();
+        assert!(verify(&params, &keypair.vk(), &message, &signature, &mut rev_state).unwrap());
+        assert_eq!(wallet.remaining(), 0);
+    }
+
+    #[test]
+    fn test_encodings_round_trip() {
+        let params = setup().unwrap();
+        let keypair = keygen(&params).unwrap();
+        let delegation = delegate(&params, &keypair.sk(), 2).unwrap();
+        let message = hash_to_field(b"pay 10 to bob");
+        let signature = delegated_sign(&params, delegation.delegation_info(), &message).unwrap();
+
+        let fr = Fr::from_hex(&message.to_hex().unwrap()).unwrap();
+        assert_eq!(fr.bytes(), message.bytes());
+        let vk = CurvePoint::from_bytes(&keypair.vk().to_bytes().unwrap()).unwrap();
+        assert_eq!((vk.x(), vk.y()), (keypair.vk().x(), keypair.vk().y()));
+
+        let token = &delegation.delegation_info()[0];
+        let decoded = SigningToken::from_hex(&token.to_hex().unwrap()).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), token.to_bytes().unwrap());
+        let decoded = DelegationRes::from_bytes(&delegation.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), delegation.to_bytes().unwrap());
+
+        let decoded = Signature::from_bytes(&signature.to_bytes().unwrap()).unwrap();
+        assert!(
+            verify(
+                &params,
+                &keypair.vk(),
+                &message,
+                &decoded,
+                &mut RevocationState::new()
+            )
+            .unwrap()
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let decoded = Signature::from_json(&signature.to_json().unwrap()).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), signature.to_bytes().unwrap());
+            let decoded = DelegationRes::from_json(&delegation.to_json().unwrap()).unwrap();
+            assert_eq!(decoded.to_bytes().unwrap(), delegation.to_bytes().unwrap());
+            let decoded = Fr::from_json(&message.to_json().unwrap()).unwrap();
+            assert_eq!(decoded.bytes(), message.bytes());
+        }
+    }
+
+    #[test]
+    fn test_parameters_are_cached() {
+        let params = setup().unwrap();
+        assert!(Arc::ptr_eq(
+            &parameters(&params).unwrap(),
+            &parameters(&params).unwrap()
+        ));
+
+        let generator = ark_grumpkin::Projective::try_from(&params).unwrap();
+        let other = CurvePoint::from(generator + generator);
+        assert!(!Arc::ptr_eq(
+            &parameters(&params).unwrap(),
+            &parameters(&other).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_curves() {
+        for name in Curve::names() {
+            let curve = Curve::new(&name).unwrap();
+            assert_eq!(curve.name(), name);
+            let params = curve.setup().unwrap();
+            let keypair = curve.keygen(&params).unwrap();
+            let delegation = curve
+                .delegate(&params, &keypair.sk(), 2, Some(1_000))
+                .unwrap();
+            let message = curve.hash_to_field(b"pay 10 to bob").unwrap();
+            let signed = curve
+                .delegated_sign(&params, &delegation.bundle(), &message, 999)
+                .unwrap();
+
+            let rev_state = curve.empty_revocation_state().unwrap();
+            let verify = |message: &[u8]| {
+                curve
+                    .verify(
+                        &params,
+                        &keypair.vk(),
+                        message,
+                        &signed.signature(),
+                        &rev_state,
+                    )
+                    .unwrap()
+            };
+            let updated = verify(&message).unwrap();
+            assert!(verify(&curve.hash_to_field(b"pay 20 to bob").unwrap()).is_none());
+
+            // The signed token and the remaining one are both in the revoked state
+            let revoked = curve
+                .revoke(&delegation.revocation_key(), &updated)
+                .unwrap();
+            assert_eq!(revoked.len(), 8 + 2 * message.len());
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Zero-knowledge proofs of a valid, unrevoked signature.
+//!
+//! A proof shows "I hold a signature on `message` under `vk` whose token identifier `m0` is not
+//! in the [`RevocationTree`] with root `root`", and reveals only a nullifier of `m0` instead of
+//! the signature. Verifiers record nullifiers, so a token still cannot be used twice, but public
+//! chains never see `m0`, `Z0` or the challenges. Proofs are Groth16 over BN254 and the
+//! signatures are those of [`crate::r1cs`], with [`Poseidon`](crate::r1cs::Poseidon) challenges.
+
+use crate::Error;
+use crate::an23_proxy_signature::{Signature, VerificationKey};
+use crate::aztec::split_scalar;
+use crate::r1cs::{ScalarVar, SignatureVar, shared_poseidon_config, verify_gadget};
+use crate::revocation_store::RevocationStore;
+use crate::revocation_tree::{
+    MAX_DEPTH, NonMembershipProof, NonMembershipProofVar, RevocationTree, enforce_non_membership,
+};
+use ark_bn254::Bn254;
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
+use ark_grumpkin::constraints::GVar;
+use ark_grumpkin::{Fq, Fr};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use rand::{CryptoRng, RngCore};
+
+pub type Proof = ark_groth16::Proof<Bn254>;
+pub type ProvingKey = ark_groth16::ProvingKey<Bn254>;
+pub type VerifyingKey = ark_groth16::VerifyingKey<Bn254>;
+
+/// Separates nullifiers from the leaves of the revocation tree, which also hash the limbs of `m0`.
+const NULLIFIER_TAG: &[u8] = b"an23-nullifier";
+
+/// The public nullifier of the token identifier `m0`.
+pub fn nullifier(m0: &Fr) -> Fq {
+    let [lo, hi] = split_scalar(*m0);
+    let mut sponge = PoseidonSponge::new(shared_poseidon_config());
+    sponge.absorb(&vec![nullifier_tag(), lo, hi]);
+    sponge.squeeze_native_field_elements(1)[0]
+}
+
+fn nullifier_tag() -> Fq {
+    Fq::from_le_bytes_mod_order(NULLIFIER_TAG)
+}
+
+/// The statement proven by [`prove`]. `vk`, `message`, `root` and the nullifier are public.
+#[derive(Clone)]
+pub struct UnrevokedSignatureCircuit {
+    pub config: PoseidonConfig<Fq>,
+    /// The depth of the revocation tree, which fixes the shape of the circuit.
+    pub depth: usize,
+    pub vk: Option<VerificationKey<ark_grumpkin::Projective>>,
+    pub message: Option<Fr>,
+    pub root: Option<Fq>,
+    pub signature: Option<Signature<ark_grumpkin::Projective>>,
+    pub non_membership: Option<NonMembershipProof>,
+}
+
+impl UnrevokedSignatureCircuit {
+    /// A circuit without assignments, for [`setup`].
+    fn blank(depth: usize) -> Self {
+        Self {
+            config: shared_poseidon_config().clone(),
+            depth,
+            vk: None,
+            message: None,
+            root: None,
+            signature: None,
+            non_membership: None,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fq> for UnrevokedSignatureCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> Result<(), SynthesisError> {
+        let missing = || SynthesisError::AssignmentMissing;
+
+        // Public inputs, in the order of `public_inputs`
+        let vk = self
+            .vk
+            .map(|vk| vk.xy().ok_or(SynthesisError::Unsatisfiable));
+        let x = FpVar::new_input(cs.clone(), || vk.ok_or_else(missing)?.map(|(x, _)| x))?;
+        let y = FpVar::new_input(cs.clone(), || vk.ok_or_else(missing)?.map(|(_, y)| y))?;
+        // Verifiers only accept keys on the curve, see `verify`
+        let vk = GVar::new(x, y, FpVar::one());
+        let message = ScalarVar::new_input(cs.clone(), || self.message.ok_or_else(missing))?;
+        let root = FpVar::new_input(cs.clone(), || self.root.ok_or_else(missing))?;
+        let expected_nullifier = FpVar::new_input(cs.clone(), || {
+            self.signature
+                .as_ref()
+                .map(|signature| nullifier(&signature.theta.m0))
+                .ok_or_else(missing)
+        })?;
+
+        let signature =
+            SignatureVar::new_witness(cs.clone(), || self.signature.clone().ok_or_else(missing))?;
+        let non_membership = NonMembershipProofVar::new_witness(
+            cs.clone(),
+            self.non_membership.as_ref(),
+            self.depth,
+        )?;
+
+        verify_gadget(cs.clone(), &self.config, &vk, &message, &signature)?;
+        enforce_non_membership(
+            cs.clone(),
+            &self.config,
+            &root,
+            &signature.m0,
+            &non_membership,
+        )?;
+
+        let [lo, hi] = signature.m0.limbs()?;
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.config);
+        sponge.absorb(&vec![FpVar::constant(nullifier_tag()), lo, hi])?;
+        sponge.squeeze_field_elements(1)?[0].enforce_equal(&expected_nullifier)
+    }
+}
+
+/// Generates the keys for revocation trees of `depth`, at most [`MAX_DEPTH`].
+pub fn setup<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    depth: usize,
+) -> Result<(ProvingKey, VerifyingKey), Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::SetupFailed);
+    }
+    Groth16::<Bn254>::circuit_specific_setup(UnrevokedSignatureCircuit::blank(depth), rng)
+        .map_err(|_| Error::SetupFailed)
+}
+
+/// Proves that `signature` is a valid signature on `message` under `vk` with a token that is not
+/// revoked in `tree`. Returns the proof and the nullifier of the token.
+pub fn prove<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    pk: &ProvingKey,
+    tree: &RevocationTree,
+    vk: &VerificationKey<ark_grumpkin::Projective>,
+    message: &Fr,
+    signature: &Signature<ark_grumpkin::Projective>,
+) -> Result<(Proof, Fq), Error> {
+    // Fail early rather than produce a proof that does not verify
+    let config = shared_poseidon_config();
+    if vk.is_zero() || !crate::r1cs::verify(config, vk, message, signature) {
+        return Err(Error::InvalidSignature);
+    }
+    let non_membership = tree.non_membership_proof(&signature.theta.m0)?;
+
+    let circuit = UnrevokedSignatureCircuit {
+        config: config.clone(),
+        depth: tree.depth(),
+        vk: Some(*vk),
+        message: Some(*message),
+        root: Some(tree.root()),
+        signature: Some(signature.clone()),
+        non_membership: Some(non_membership),
+    };
+    let proof = Groth16::<Bn254>::prove(pk, circuit, rng).map_err(|_| Error::ProofFailed)?;
+    Ok((proof, nullifier(&signature.theta.m0)))
+}
+
+/// Verifies a proof and records its nullifier in `spent`, like
+/// [`AN23ProxySignature::verify_with_store`](crate::an23_proxy_signature::AN23ProxySignature::verify_with_store).
+/// Fails with [`Error::UseOfRevokedToken`] if the nullifier was already recorded.
+pub fn verify(
+    verifying_key: &VerifyingKey,
+    vk: &VerificationKey<ark_grumpkin::Projective>,
+    message: &Fr,
+    root: &Fq,
+    nullifier: &Fq,
+    proof: &Proof,
+    spent: &mut impl RevocationStore<Fq>,
+) -> Result<bool, Error> {
+    if spent.contains(nullifier)? {
+        return Err(Error::UseOfRevokedToken);
+    }
+    let Some(inputs) = public_inputs(vk, message, root, nullifier) else {
+        return Ok(false);
+    };
+    if !Groth16::<Bn254>::verify(verifying_key, &inputs, proof).map_err(|_| Error::ProofFailed)? {
+        return Ok(false);
+    }
+    if !spent.insert_if_absent(nullifier)? {
+        return Err(Error::UseOfRevokedToken);
+    }
+    Ok(true)
+}
+
+fn public_inputs(
+    vk: &VerificationKey<ark_grumpkin::Projective>,
+    message: &Fr,
+    root: &Fq,
+    nullifier: &Fq,
+) -> Option<Vec<Fq>> {
+    // `xy` rejects the point at infinity; deserialized keys are on the curve
+    let (x, y) = vk.xy()?;
+    let [lo, hi] = split_scalar(*message);
+    Some(vec![x, y, lo, hi, *root, *nullifier])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxySignature;
+    use crate::an23_proxy_signature::{AN23ProxySignature, DelegationSpec};
+    use crate::r1cs::Poseidon;
+    use ark_ff::UniformRand;
+    use ark_grumpkin::Projective;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_prove_unrevoked_signature() {
+        // Groth16 needs a cryptographic RNG
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        type Scheme = AN23ProxySignature<Projective, Poseidon>;
+        let parameters = Scheme::setup(&mut rng).unwrap();
+        let (sk, vk) = Scheme::keygen(&mut rng, &parameters).unwrap();
+        let (mut tokens, _) = Scheme::delegate(
+            &mut rng,
+            &parameters,
+            &sk,
+            &DelegationSpec {
+                number_of_tokens: 2,
+                bind_message_nonce: false,
+            },
+        )
+        .unwrap();
+        let unused = tokens.remove(0);
+        let message = Fr::rand(&mut rng);
+        let signature =
+            Scheme::delegated_sign(&mut rng, &parameters, &mut tokens, &message).unwrap();
+
+        let mut tree = RevocationTree::new(4).unwrap();
+        tree.insert_if_absent(&unused.m0).unwrap();
+        let (pk, verifying_key) = setup(&mut rng, tree.depth()).unwrap();
+
+        let (proof, nullifier) = prove(&mut rng, &pk, &tree, &vk, &message, &signature).unwrap();
+        let root = tree.root();
+        assert!(
+            !verify(
+                &verifying_key,
+                &vk,
+                &(message + Fr::from(1u64)),
+                &root,
+                &nullifier,
+                &proof,
+                &mut Vec::new()
+            )
+            .unwrap()
+        );
+
+        let mut spent = Vec::new();
+        assert!(
+            verify(
+                &verifying_key,
+                &vk,
+                &message,
+                &root,
+                &nullifier,
+                &proof,
+                &mut spent
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            verify(
+                &verifying_key,
+                &vk,
+                &message,
+                &root,
+                &nullifier,
+                &proof,
+                &mut spent
+            ),
+            Err(Error::UseOfRevokedToken)
+        );
+
+        // Revoked tokens and invalid signatures cannot be proven
+        tree.insert_if_absent(&signature.theta.m0).unwrap();
+        assert_eq!(
+            prove(&mut rng, &pk, &tree, &vk, &message, &signature).unwrap_err(),
+            Error::UseOfRevokedToken
+        );
+        assert_eq!(
+            prove(&mut rng, &pk, &tree, &vk, &Fr::from(1u64), &signature).unwrap_err(),
+            Error::InvalidSignature
+        );
+    }
+}
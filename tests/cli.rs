@@ -0,0 +1,199 @@
+//! End-to-end tests of the `schnorr-tokens` binary.
+#![cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+
+fn cli(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_schnorr-tokens"));
+    command.current_dir(dir);
+    command
+}
+
+/// Sets up parameters, a key pair and a delegation of `tokens` tokens in `dir`.
+fn delegate(dir: &Path, tokens: u64) {
+    cli(dir).args(["setup", "-o", "params"]).assert().success();
+    cli(dir)
+        .args([
+            "keygen",
+            "--params",
+            "params",
+            "--secret-key",
+            "sk",
+            "-o",
+            "vk",
+        ])
+        .assert()
+        .success();
+    cli(dir)
+        .args(["delegate", "--params", "params", "--secret-key", "sk"])
+        .args(["--tokens", &tokens.to_string()])
+        .args(["--revocation-key", "rev-key", "-o", "bundle"])
+        .assert()
+        .success();
+}
+
+fn delegated_sign(dir: &Path, message: &str, output: &str) {
+    cli(dir)
+        .args(["delegated-sign", "--params", "params", "--bundle", "bundle"])
+        .args(["--message-text", message, "-o", output])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("token(s) left"));
+}
+
+fn verify(dir: &Path, message: &str, signature: &str) -> assert_cmd::assert::Assert {
+    cli(dir)
+        .args(["verify", "--params", "params", "--verification-key", "vk"])
+        .args(["--signature", signature, "--message-text", message])
+        .args(["--revocation-state", "rev-state"])
+        .assert()
+}
+
+#[test]
+fn test_verify_exit_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    delegate(dir.path(), 2);
+    delegated_sign(dir.path(), "hello", "sig");
+
+    // A wrong message is rejected without touching the revocation state
+    verify(dir.path(), "goodbye", "sig").code(1);
+    assert!(!dir.path().join("rev-state").exists());
+
+    verify(dir.path(), "hello", "sig").code(0).stdout("valid\n");
+    // The token is now in the revocation state
+    verify(dir.path(), "hello", "sig")
+        .code(3)
+        .stderr(predicate::str::contains("UseOfRevokedToken"));
+
+    // Revoking the delegation rejects its other token
+    delegated_sign(dir.path(), "hello again", "sig2");
+    cli(dir.path())
+        .args(["revoke", "--revocation-key", "rev-key"])
+        .args(["--revocation-state", "rev-state"])
+        .assert()
+        .success();
+    verify(dir.path(), "hello again", "sig2").code(3);
+
+    // Only the state and its lock file are left behind by the atomic writes
+    let leftovers = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter(|entry| {
+            let name = entry.as_ref().unwrap().file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("rev-state.") && name != "rev-state.lock"
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+#[test]
+fn test_json_encoding() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = cli(dir.path())
+        .args(["--encoding", "json", "setup"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["type"], "parameters");
+    assert!(hex::decode(json["data"].as_str().unwrap()).is_ok());
+    std::fs::write(dir.path().join("params"), &output).unwrap();
+
+    // The type is checked when decoding
+    cli(dir.path())
+        .args([
+            "--encoding",
+            "json",
+            "inspect",
+            "verification-key",
+            "params",
+        ])
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("expected a verification-key"));
+    cli(dir.path())
+        .args(["--encoding", "json", "inspect", "parameters", "params"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("generator: "));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_secret_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    // An existing file is replaced, so a handle opened before never sees the secret
+    std::fs::write(dir.path().join("sk"), "").unwrap();
+    let mut old = std::fs::File::open(dir.path().join("sk")).unwrap();
+    delegate(dir.path(), 1);
+    let mut contents = String::new();
+    std::io::Read::read_to_string(&mut old, &mut contents).unwrap();
+    assert!(contents.is_empty());
+
+    let mode = |name: &str| {
+        std::fs::metadata(dir.path().join(name))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    };
+    assert_eq!(mode("sk"), 0o600);
+    assert_eq!(mode("bundle"), 0o600);
+}
+
+#[test]
+fn test_revocation_state_is_locked() {
+    let dir = tempfile::tempdir().unwrap();
+    delegate(dir.path(), 1);
+    delegated_sign(dir.path(), "hello", "sig");
+
+    // A concurrent verify holds the lock until it has recorded the token
+    let lock = std::fs::File::create(dir.path().join("rev-state.lock")).unwrap();
+    lock.lock().unwrap();
+    verify(dir.path(), "hello", "sig")
+        .failure()
+        .stderr(predicate::str::contains("in use"));
+    assert!(!dir.path().join("rev-state").exists());
+
+    drop(lock);
+    verify(dir.path(), "hello", "sig").code(0).stdout("valid\n");
+}
+
+#[test]
+fn test_challenge_hashes() {
+    let dir = tempfile::tempdir().unwrap();
+    delegate(dir.path(), 1);
+    cli(dir.path())
+        .args(["--hash", "poseidon2", "delegate", "--params", "params"])
+        .args(["--secret-key", "sk", "--tokens", "1"])
+        .args(["--revocation-key", "rev-key", "-o", "bundle"])
+        .assert()
+        .success();
+    cli(dir.path())
+        .args([
+            "--hash",
+            "poseidon2",
+            "delegated-sign",
+            "--params",
+            "params",
+        ])
+        .args(["--bundle", "bundle", "--message-text", "hello", "-o", "sig"])
+        .assert()
+        .success();
+
+    // The signature only verifies with the hash it was made with
+    verify(dir.path(), "hello", "sig").code(1);
+    cli(dir.path())
+        .args(["--hash", "poseidon2", "verify", "--params", "params"])
+        .args(["--verification-key", "vk", "--signature", "sig"])
+        .args(["--message-text", "hello"])
+        .assert()
+        .code(0)
+        .stdout("valid\n");
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title AN23 proxy signature verifier over bn254, generated by schnorr-tokens.
+/// @notice Challenges are SHA-256, as in `challenge_hash::Sha256`. Verification is stateless:
+/// record `signature.m0` to reject revoked and reused tokens.
+contract AN23Verifier {
+    struct Signature {
+        uint256 c0;
+        uint256 c1;
+        uint256 z1;
+        uint256 m0;
+        uint256 Z0x;
+        uint256 Z0y;
+    }
+
+    // The base field modulus
+    uint256 constant P = 0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47;
+    // The scalar field modulus
+    uint256 constant N = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
+    uint256 constant GX = 0x0000000000000000000000000000000000000000000000000000000000000001;
+    uint256 constant GY = 0x0000000000000000000000000000000000000000000000000000000000000002;
+
+    /// Whether `signature` signs `message` under `vk`.
+    function verify(uint256[2] calldata vk, uint256 message, Signature calldata signature)
+        external
+        view
+        returns (bool)
+    {
+        if (
+            message >= N || signature.c0 >= N || signature.c1 >= N || signature.z1 >= N
+                || signature.m0 >= N
+        ) {
+            return false;
+        }
+        uint256[2] memory z0 = [signature.Z0x, signature.Z0y];
+        uint256[2] memory zero;
+        // R0 = [-c0]X + Z0
+        (bool ok0, uint256[2] memory r0) = mulAdd(vk, N - signature.c0, z0);
+        // R1 = [-c1]Z0 + [z1]G
+        (bool ok1, uint256[2] memory z1g) = mulAdd([GX, GY], signature.z1, zero);
+        (bool ok2, uint256[2] memory r1) = mulAdd(z0, N - signature.c1, z1g);
+        return ok0 && ok1 && ok2 && signature.c0 == challenge(signature.m0, vk, r0)
+            && signature.c1 == challenge(message, z0, r1);
+    }
+
+    /// `[s]p + q` with the ecMul and ecAdd precompiles, or `ok = false` if a point is not on the
+    /// curve. `(0, 0)` is the point at infinity.
+    function mulAdd(uint256[2] memory p, uint256 s, uint256[2] memory q)
+        internal
+        view
+        returns (bool ok, uint256[2] memory r)
+    {
+        uint256[3] memory mulInput = [p[0], p[1], s];
+        assembly {
+            ok := staticcall(gas(), 0x07, mulInput, 0x60, r, 0x40)
+        }
+        if (!ok) {
+            return (false, r);
+        }
+        uint256[4] memory addInput = [r[0], r[1], q[0], q[1]];
+        assembly {
+            ok := staticcall(gas(), 0x06, addInput, 0x80, r, 0x40)
+        }
+    }
+
+    /// `challenge_hash::Sha256` of a scalar and two points: SHA-256 of their little-endian
+    /// encodings, with the last byte of the digest cleared, as a little-endian integer mod N.
+    function challenge(uint256 f, uint256[2] memory a, uint256[2] memory b)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256[5] memory input =
+            [reverseBytes(f), reverseBytes(a[0]), reverseBytes(a[1]), reverseBytes(b[0]), reverseBytes(b[1])];
+        uint256[1] memory digest;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x02, input, 0xa0, digest, 0x20)
+        }
+        require(ok, "sha256 failed");
+        return (reverseBytes(digest[0]) & type(uint248).max) % N;
+    }
+
+    /// Converts between big-endian words and little-endian encodings.
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v = ((v >> 8) & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v >> 16) & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v >> 32) & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v >> 64) & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+}
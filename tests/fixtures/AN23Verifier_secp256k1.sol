@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title AN23 proxy signature verifier over secp256k1, generated by schnorr-tokens.
+/// @notice Challenges are SHA-256, as in `challenge_hash::Sha256`. Verification is stateless:
+/// record `signature.m0` to reject revoked and reused tokens.
+contract AN23Verifier {
+    struct Signature {
+        uint256 c0;
+        uint256 c1;
+        uint256 z1;
+        uint256 m0;
+        uint256 Z0x;
+        uint256 Z0y;
+    }
+
+    // The base field modulus
+    uint256 constant P = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f;
+    // The scalar field modulus
+    uint256 constant N = 0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141;
+    uint256 constant GX = 0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798;
+    uint256 constant GY = 0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8;
+
+    /// Whether `signature` signs `message` under `vk`, given `r0 = Z0 - [c0]vk` and
+    /// `r1 = [z1]G - [c1]Z0`.
+    function verify(
+        uint256[2] calldata vk,
+        uint256 message,
+        Signature calldata signature,
+        uint256[2] calldata r0,
+        uint256[2] calldata r1
+    ) external view returns (bool) {
+        if (
+            message >= N || signature.c0 >= N || signature.c1 >= N || signature.z1 >= N
+                || signature.m0 >= N
+        ) {
+            return false;
+        }
+        uint256[2] memory z0 = [signature.Z0x, signature.Z0y];
+        if (!onCurve(vk) || !onCurve(z0) || !onCurve(r0) || !onCurve(r1)) {
+            return false;
+        }
+        // [c0]X = Z0 - R0
+        (bool ok, uint256[2] memory d) = subtract(z0, r0);
+        if (!ok || mulAddAddress(vk, signature.c0, 0) != pointAddress(d)) {
+            return false;
+        }
+        // R1 = [-c1]Z0 + [z1]G
+        if (mulAddAddress(z0, N - signature.c1, signature.z1) != pointAddress(r1)) {
+            return false;
+        }
+        return signature.c0 == challenge(signature.m0, vk, r0)
+            && signature.c1 == challenge(message, z0, r1);
+    }
+
+    /// The address of `[a]q + [b]G`. With `r = q.x` and `v` the parity of `q.y`,
+    /// `ecrecover(h, v, r, s)` is the address of `[s/r]q - [h/r]G`. Zero if `q.x >= N` or the
+    /// result is the point at infinity.
+    function mulAddAddress(uint256[2] memory q, uint256 a, uint256 b) internal view returns (address) {
+        if (q[0] >= N) {
+            return address(0);
+        }
+        uint256 s = mulmod(a, q[0], N);
+        uint256 h = (N - mulmod(b, q[0], N)) % N;
+        uint256[4] memory input = [h, 27 + (q[1] & 1), q[0], s];
+        uint256[1] memory signer;
+        assembly {
+            pop(staticcall(gas(), 0x01, input, 0x80, signer, 0x20))
+        }
+        return address(uint160(signer[0]));
+    }
+
+    /// The Ethereum address of a public key, never zero.
+    function pointAddress(uint256[2] memory p) internal pure returns (address) {
+        bytes32 digest;
+        assembly {
+            digest := keccak256(p, 0x40)
+        }
+        return address(uint160(uint256(digest)));
+    }
+
+    function onCurve(uint256[2] memory p) internal pure returns (bool) {
+        return p[0] < P && p[1] < P
+            && mulmod(p[1], p[1], P) == addmod(mulmod(mulmod(p[0], p[0], P), p[0], P), 7, P);
+    }
+
+    /// `p - q` for points on the curve, or `ok = false` if `p.x = q.x`.
+    function subtract(uint256[2] memory p, uint256[2] memory q)
+        internal
+        view
+        returns (bool ok, uint256[2] memory r)
+    {
+        if (p[0] == q[0]) {
+            return (false, r);
+        }
+        // The chord through p and -q
+        uint256 lambda = mulmod(addmod(p[1], q[1], P), inverse(addmod(p[0], P - q[0], P)), P);
+        r[0] = addmod(mulmod(lambda, lambda, P), P - addmod(p[0], q[0], P), P);
+        r[1] = addmod(mulmod(lambda, addmod(p[0], P - r[0], P), P), P - p[1], P);
+        return (true, r);
+    }
+
+    /// `x^(P - 2)` with the modexp precompile.
+    function inverse(uint256 x) internal view returns (uint256) {
+        uint256[6] memory input = [uint256(32), 32, 32, x, P - 2, P];
+        uint256[1] memory out;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x05, input, 0xc0, out, 0x20)
+        }
+        require(ok, "modexp failed");
+        return out[0];
+    }
+
+    /// `challenge_hash::Sha256` of a scalar and two points: SHA-256 of their little-endian
+    /// encodings, with the last byte of the digest cleared, as a little-endian integer mod N.
+    function challenge(uint256 f, uint256[2] memory a, uint256[2] memory b)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256[5] memory input =
+            [reverseBytes(f), reverseBytes(a[0]), reverseBytes(a[1]), reverseBytes(b[0]), reverseBytes(b[1])];
+        uint256[1] memory digest;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x02, input, 0xa0, digest, 0x20)
+        }
+        require(ok, "sha256 failed");
+        return (reverseBytes(digest[0]) & type(uint248).max) % N;
+    }
+
+    /// Converts between big-endian words and little-endian encodings.
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v = ((v >> 8) & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v >> 16) & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v >> 32) & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v >> 64) & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+}
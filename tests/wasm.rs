@@ -0,0 +1,181 @@
+//! Malformed inputs to the JS bindings, which must throw. `JsError`s can only be constructed on
+//! wasm, so these run under `wasm-bindgen-test-runner`, see the README. With the `r1cs` feature,
+//! they also cover the revocation tree on 32-bit `usize`.
+#![cfg(target_arch = "wasm32")]
+
+use ark_ff::{BigInteger, PrimeField};
+use schnorr_tokens::wasm_bindings::{
+    Curve, CurvePoint, DelegationWallet, Fr, RevocationState, Signature, delegate, keygen, setup,
+    verify,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn modulus() -> Vec<u8> {
+    ark_grumpkin::Fr::MODULUS.to_bytes_le()
+}
+
+#[wasm_bindgen_test]
+fn test_scalars_must_have_32_bytes() {
+    assert!(Fr::new(vec![1; 32]).is_ok());
+    assert!(Fr::new(vec![1; 31]).is_err());
+    assert!(Fr::new(vec![1; 33]).is_err());
+    assert!(Fr::new(vec![]).is_err());
+    assert!(Fr::from_bytes(&[1; 33]).is_err());
+    assert!(Fr::from_hex("0x01").is_err());
+    assert!(Fr::from_hex("not hex").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_scalars_must_be_canonical() {
+    let mut largest = modulus();
+    largest[0] -= 1;
+    assert!(Fr::new(largest).is_ok());
+    assert!(Fr::new(modulus()).is_err());
+    assert!(Fr::new(vec![0xff; 32]).is_err());
+    assert!(Fr::from_bytes(&modulus()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_points_must_be_on_the_curve() {
+    let generator = setup().unwrap();
+    assert!(CurvePoint::new(generator.x(), generator.y()).is_ok());
+
+    let mut y = generator.y();
+    y[0] ^= 1;
+    assert!(CurvePoint::new(generator.x(), y).is_err());
+    assert!(CurvePoint::new(generator.x(), generator.y()[..31].to_vec()).is_err());
+    assert!(CurvePoint::new(vec![1; 32], vec![1; 32]).is_err());
+    // Coordinates are base field elements, which must be canonical too
+    assert!(CurvePoint::new(vec![0xff; 32], generator.y()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_identity_is_not_a_generator() {
+    let identity = CurvePoint::new(vec![0; 32], vec![0; 32]).unwrap();
+    assert!(keygen(&identity).is_err());
+
+    let curve = Curve::new("grumpkin").unwrap();
+    let mut params = curve.setup().unwrap();
+    assert!(curve.keygen(&params).is_ok());
+    // The compressed identity: zero with the infinity flag set
+    params.iter_mut().for_each(|byte| *byte = 0);
+    *params.last_mut().unwrap() = 0x40;
+    assert!(curve.keygen(&params).is_err());
+    assert!(curve.keygen(&params[..31]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_malformed_signatures_and_states_throw() {
+    let params = setup().unwrap();
+    let keypair = keygen(&params).unwrap();
+    let message = Fr::new(vec![7; 32]).unwrap();
+    let scalar = || Fr::new(vec![1; 32]).unwrap();
+    let signature = Signature::new(scalar(), scalar(), scalar(), scalar(), params.clone()).unwrap();
+
+    // Invalid signatures are rejected without throwing
+    let mut rev_state = RevocationState::new();
+    assert!(!verify(&params, &keypair.vk(), &message, &signature, &mut rev_state).unwrap());
+
+    let mut bytes = signature.to_bytes().unwrap();
+    assert!(Signature::from_bytes(&bytes[1..]).is_err());
+    bytes.push(0);
+    assert!(Signature::from_bytes(&bytes).is_err());
+
+    assert!(RevocationState::from_bytes(&[1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    assert!(
+        RevocationState::from_bytes(&[[1, 0, 0, 0, 0, 0, 0, 0].as_slice(), &modulus()].concat())
+            .is_err()
+    );
+    assert!(Curve::new("ed448").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_wallet_signs_only_reserved_tokens() {
+    let params = setup().unwrap();
+    let keypair = keygen(&params).unwrap();
+    let delegation = delegate(&params, &keypair.sk(), 1).unwrap();
+    let mut wallet = DelegationWallet::new(delegation.delegation_info(), Some(1_000)).unwrap();
+    let message = Fr::new(vec![7; 32]).unwrap();
+
+    assert!(wallet.sign_reserved(&params, &message, 0).is_err());
+    assert!(wallet.reserve(1_000).is_err());
+    wallet.reserve(0).unwrap();
+    wallet.sign_reserved(&params, &message, 0).unwrap();
+    assert!(wallet.sign_reserved(&params, &message, 0).is_err());
+    assert!(wallet.reserve(0).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_wallet_reserved_tokens_expire() {
+    let params = setup().unwrap();
+    let keypair = keygen(&params).unwrap();
+    let delegation = delegate(&params, &keypair.sk(), 2).unwrap();
+    let mut wallet = DelegationWallet::new(delegation.delegation_info(), Some(1_000)).unwrap();
+    let message = Fr::new(vec![7; 32]).unwrap();
+
+    // A token reserved before the expiry cannot be signed with after it, and stays used
+    wallet.reserve(999).unwrap();
+    assert!(wallet.sign_reserved(&params, &message, 1_000).is_err());
+    assert!(wallet.sign_reserved(&params, &message, 999).is_err());
+    assert_eq!(wallet.remaining(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_curve_bundles_expire() {
+    let curve = Curve::new("grumpkin").unwrap();
+    let params = curve.setup().unwrap();
+    let keypair = curve.keygen(&params).unwrap();
+    let delegation = curve
+        .delegate(&params, &keypair.sk(), 2, Some(1_000))
+        .unwrap();
+    let message = curve.hash_to_field(b"pay 10 to bob").unwrap();
+
+    assert!(
+        curve
+            .delegated_sign(&params, &delegation.bundle(), &message, 1_000)
+            .is_err()
+    );
+    let signed = curve
+        .delegated_sign(&params, &delegation.bundle(), &message, 999)
+        .unwrap();
+    assert!(
+        curve
+            .delegated_sign(&params, &signed.bundle(), &message, 1_001)
+            .is_err()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_json_must_hold_valid_values() {
+    let params = setup().unwrap();
+    let json = params.to_json().unwrap();
+    assert_eq!(CurvePoint::from_json(&json).unwrap().x(), params.x());
+    assert!(CurvePoint::from_json("\"00\"").is_err());
+    assert!(CurvePoint::from_json("not json").is_err());
+
+    let keypair = keygen(&params).unwrap();
+    let message = Fr::new(vec![7; 32]).unwrap();
+    let signature =
+        schnorr_tokens::wasm_bindings::sign(&params, &keypair.sk(), &message, None).unwrap();
+    let json = signature.to_json().unwrap();
+    assert_eq!(
+        Signature::from_json(&json).unwrap().to_bytes().unwrap(),
+        signature.to_bytes().unwrap()
+    );
+    let m0 = signature.theta_m0().to_hex().unwrap();
+    assert!(Signature::from_json(&json.replacen(&m0, "00", 1)).is_err());
+    assert!(Signature::from_json("{}").is_err());
+}
+
+#[cfg(feature = "r1cs")]
+#[wasm_bindgen_test]
+fn test_deepest_revocation_tree() {
+    use schnorr_tokens::revocation_store::RevocationStore;
+    use schnorr_tokens::revocation_tree::{MAX_DEPTH, RevocationTree};
+
+    let mut tree = RevocationTree::new(MAX_DEPTH).unwrap();
+    assert!(
+        tree.insert_if_absent(&ark_grumpkin::Fr::from(1u64))
+            .unwrap()
+    );
+}